@@ -0,0 +1,158 @@
+use crate::errors::ErrorMsg;
+use chrono::{DateTime, TimeZone, Utc};
+
+const CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const SIGNATURE_WORDS: usize = 104;
+const CHECKSUM_WORDS: usize = 6;
+const DEFAULT_EXPIRY: u64 = 3600;
+
+/// The parts of a BOLT11 invoice that are useful when tracking a charge or payment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DecodedInvoice {
+    /// Amount encoded in the invoice, `None` for amountless invoices.
+    pub amount_msats: Option<u64>,
+    /// Hex encoded payment hash.
+    pub payment_hash: String,
+    /// Unix timestamp of when the invoice was created.
+    pub timestamp: u64,
+    /// Seconds after `timestamp` until the invoice expires.
+    pub expiry: u64,
+}
+
+impl DecodedInvoice {
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let expires = self.timestamp.checked_add(self.expiry)?;
+        Utc.timestamp_opt(i64::try_from(expires).ok()?, 0).single()
+    }
+}
+
+/// Decodes a BOLT11 invoice locally, without verifying its signature.
+pub fn decode<T>(invoice: T) -> Result<DecodedInvoice, ErrorMsg>
+where
+    T: AsRef<str>,
+{
+    let invoice = invoice.as_ref().trim().to_lowercase();
+    let invoice = invoice.strip_prefix("lightning:").unwrap_or(&invoice);
+    let bad = |reason: &str| ErrorMsg::BadInvoice(reason.to_string());
+
+    let (hrp, data) = invoice
+        .rsplit_once('1')
+        .ok_or_else(|| bad("missing bech32 separator"))?;
+    if !hrp.starts_with("ln") {
+        return Err(bad("missing ln prefix"));
+    }
+
+    let words = data
+        .chars()
+        .map(|c| CHARSET.find(c).map(|w| w as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| bad("invalid bech32 character"))?;
+    if words.len() < 7 + SIGNATURE_WORDS + CHECKSUM_WORDS {
+        return Err(bad("too short"));
+    }
+    if !verify_checksum(hrp, &words) {
+        return Err(bad("invalid checksum"));
+    }
+
+    let amount_msats = parse_amount(&hrp[2..]).ok_or_else(|| bad("invalid amount"))?;
+    let words = &words[..words.len() - CHECKSUM_WORDS - SIGNATURE_WORDS];
+    let timestamp = words_to_u64(&words[..7]);
+
+    let mut payment_hash = None;
+    let mut expiry = DEFAULT_EXPIRY;
+    let mut fields = &words[7..];
+    while fields.len() >= 3 {
+        let tag = fields[0];
+        let len = (fields[1] as usize) * 32 + fields[2] as usize;
+        let value = fields
+            .get(3..3 + len)
+            .ok_or_else(|| bad("truncated tagged field"))?;
+        match tag {
+            // 'p'
+            1 if len == 52 => {
+                let bytes = words_to_bytes(value);
+                payment_hash = Some(bytes[..32].iter().map(|b| format!("{:02x}", b)).collect());
+            }
+            // 'x'
+            6 => expiry = words_to_u64(value),
+            _ => {}
+        }
+        fields = &fields[3 + len..];
+    }
+
+    Ok(DecodedInvoice {
+        amount_msats,
+        payment_hash: payment_hash.ok_or_else(|| bad("missing payment hash"))?,
+        timestamp,
+        expiry,
+    })
+}
+
+/// Parses the amount in the human readable part, after the `ln` prefix.
+/// Returns `Some(None)` when the invoice has no amount.
+fn parse_amount(hrp: &str) -> Option<Option<u64>> {
+    let start = match hrp.find(|c: char| c.is_ascii_digit()) {
+        Some(start) => start,
+        None => return Some(None),
+    };
+    let amount = &hrp[start..];
+    let (digits, multiplier) = match amount.chars().last()? {
+        'm' | 'u' | 'n' | 'p' => amount.split_at(amount.len() - 1),
+        _ => (amount, ""),
+    };
+    let value = digits.parse::<u64>().ok()?;
+    let msats = match multiplier {
+        "m" => value.checked_mul(100_000_000)?,
+        "u" => value.checked_mul(100_000)?,
+        "n" => value.checked_mul(100)?,
+        "p" if value % 10 == 0 => value / 10,
+        "" => value.checked_mul(100_000_000_000)?,
+        _ => return None,
+    };
+    Some(Some(msats))
+}
+
+fn words_to_u64(words: &[u8]) -> u64 {
+    words.iter().fold(0, |acc, w| (acc << 5) | u64::from(*w))
+}
+
+fn words_to_bytes(words: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(words.len() * 5 / 8);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for w in words {
+        acc = (acc << 5) | u32::from(*w);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    bytes
+}
+
+fn verify_checksum(hrp: &str, words: &[u8]) -> bool {
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 31));
+    values.extend_from_slice(words);
+    polymod(&values) == 1
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ u32::from(*v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+#[cfg(test)]
+mod tests;
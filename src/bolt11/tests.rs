@@ -0,0 +1,29 @@
+use super::*;
+
+const INVOICE: &str = "lnbc120n1p0tdjwmpp5ycws0d788cjeqp9rn2wwxfymrekj9n80wy2yrk66tuu3ga5wukfsdzq2pshjmt9de6zqen0wgsrzv3qwp5hsetvwvsxzapqwdshgmmndp5hxtnsd3skxefwxqzjccqp2sp5vnsvmjlu6hrfegcdjs47njrga36g3x45wfmqjjjlerwgagj62yysrzjq2v4aw4gy7m93en32dcaplym056zezcljdjshyk8yakwtsp2h4yvcz9atuqqhtsqqqqqqqlgqqqqqqgqjq9qy9qsqhykfacrdy06cuyegvt4p50su53qwgrqn5jf6d83fd0upsa4frpxqnm2zl323zuvmz5ypv9gh9nr3jav6u2ccwkpd56h3n6l3ja5q7wgpxudlv4";
+
+#[test]
+fn test_decode_invoice() {
+    let decoded = decode(INVOICE).unwrap();
+
+    assert_eq!(decoded.amount_msats, Some(12_000));
+    assert_eq!(decoded.payment_hash.len(), 64);
+    assert!(decoded.expires_at().is_some());
+}
+
+#[test]
+fn test_decode_bad_checksum() {
+    let mut invoice = INVOICE.to_string();
+    invoice.pop();
+    invoice.push('q');
+
+    assert!(decode(invoice).is_err());
+}
+
+#[test]
+fn test_parse_amount() {
+    assert_eq!(parse_amount("bc"), Some(None));
+    assert_eq!(parse_amount("bc2500u"), Some(Some(250_000_000)));
+    assert_eq!(parse_amount("tb10p"), Some(Some(1)));
+    assert_eq!(parse_amount("bc1p"), None);
+}
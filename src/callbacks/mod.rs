@@ -0,0 +1,6 @@
+mod types;
+mod webhook;
+pub use types::*;
+pub use webhook::*;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,89 @@
+use crate::{
+    charges::{ChargeTransaction, InvoiceData},
+    custom_deserializer::deserialize_lenient_datetime,
+    models::{Amount, ChargeStatus},
+};
+#[cfg(feature = "withdrawal_request")]
+use crate::{
+    models::WithdrawalStatus,
+    withdrawal_request::{WithdrawInvoiceData, WithdrawalRequestsData},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Body ZEBEDEE POSTs to a Charge's `callbackUrl` whenever the Charge is updated.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
+pub struct ChargeCallback {
+    pub id: String,
+    pub unit: String,
+    pub amount: String,
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
+    pub created_at: Option<DateTime<Utc>>,
+    pub callback_url: Option<String>,
+    pub internal_id: Option<String>,
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub status: ChargeStatus,
+    pub invoice: Option<InvoiceData>,
+    /// Settlement transaction, present once the Charge has been paid.
+    pub transaction: Option<ChargeTransaction>,
+}
+
+impl ChargeCallback {
+    /// The charge amount with its unit, `None` when either can't be parsed.
+    pub fn amount(&self) -> Option<Amount> {
+        Amount::parse(&self.amount, &self.unit)
+    }
+}
+
+/// Body ZEBEDEE POSTs to a Withdrawal Request's `callbackUrl` whenever it is updated.
+#[cfg(feature = "withdrawal_request")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalCallback {
+    pub id: String,
+    pub unit: String,
+    pub amount: String,
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub internal_id: Option<String>,
+    pub description: Option<String>,
+    pub callback_url: Option<String>,
+    pub status: WithdrawalStatus,
+    pub invoice: WithdrawInvoiceData,
+}
+
+#[cfg(feature = "withdrawal_request")]
+impl WithdrawalCallback {
+    /// The withdrawal amount with its unit, `None` when either can't be parsed.
+    pub fn amount(&self) -> Option<Amount> {
+        Amount::parse(&self.amount, &self.unit)
+    }
+}
+
+/// The callback body ZEBEDEE would send for this Withdrawal Request in its current state.
+#[cfg(feature = "withdrawal_request")]
+impl From<WithdrawalRequestsData> for WithdrawalCallback {
+    fn from(value: WithdrawalRequestsData) -> Self {
+        WithdrawalCallback {
+            id: value.id,
+            unit: value.unit,
+            amount: value.amount,
+            created_at: Some(value.created_at),
+            expires_at: Some(value.expires_at),
+            internal_id: Some(value.internal_id),
+            description: Some(value.description),
+            callback_url: value.callback_url,
+            status: value.status,
+            invoice: value.invoice,
+        }
+    }
+}
@@ -1,6 +1,17 @@
 use super::*;
-use crate::ZebedeeClient;
-use std::env;
+use crate::{
+    errors::{ErrorMsg, ZebedeeError},
+    logging::{LogJson, Redact},
+    models::{
+        format_amount, parse_amount, Amount, ChargeStatus, ListParams, Msats, UnitType, UsdAmount,
+    },
+    polling::PollBudget,
+    test_utils::{MockResponse, MockServer},
+    ZebedeeClient,
+};
+use futures::StreamExt;
+use std::{env, time::Duration};
+use validator::Validate;
 
 #[tokio::test]
 async fn test_create_charge() {
@@ -24,7 +35,7 @@ async fn test_get_charges() {
         env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
     let zebedee_client = ZebedeeClient::new().domain(zbdenv).apikey(apikey).build();
 
-    let r = zebedee_client.get_charges().await.unwrap();
+    let r = zebedee_client.get_charges(None).await.unwrap();
     assert!(r.success);
 }
 #[tokio::test]
@@ -46,3 +57,866 @@ async fn test_get_charge() {
         .unwrap();
     assert!(r2.success);
 }
+
+#[tokio::test]
+async fn test_usd_charge() {
+    let charge = Charge::usd(UsdAmount::from_dollars(12.345));
+    let body = serde_json::to_value(&charge).unwrap();
+
+    assert_eq!(body["unit"], "usd");
+    assert_eq!(body["amount"], "1235");
+
+    let data = ChargesData {
+        unit: String::from("usd"),
+        amount: String::from("1235"),
+        ..Default::default()
+    };
+    assert_eq!(data.usd_amount(), Some(UsdAmount(1235)));
+    assert_eq!(data.usd_amount().unwrap().to_string(), "$12.35");
+}
+
+#[tokio::test]
+async fn test_decoded_invoice() {
+    let data = ChargesData {
+        invoice: Some(InvoiceData {
+            request: String::from("lnbc120n1p0tdjwmpp5ycws0d788cjeqp9rn2wwxfymrekj9n80wy2yrk66tuu3ga5wukfsdzq2pshjmt9de6zqen0wgsrzv3qwp5hsetvwvsxzapqwdshgmmndp5hxtnsd3skxefwxqzjccqp2sp5vnsvmjlu6hrfegcdjs47njrga36g3x45wfmqjjjlerwgagj62yysrzjq2v4aw4gy7m93en32dcaplym056zezcljdjshyk8yakwtsp2h4yvcz9atuqqhtsqqqqqqqlgqqqqqqgqjq9qy9qsqhykfacrdy06cuyegvt4p50su53qwgrqn5jf6d83fd0upsa4frpxqnm2zl323zuvmz5ypv9gh9nr3jav6u2ccwkpd56h3n6l3ja5q7wgpxudlv4"),
+            uri: String::from(""),
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(data.decoded_invoice().unwrap().amount_msats, Some(12_000));
+    assert_eq!(data.decoded_invoice(), data.clone().decoded_invoice());
+    // a changed invoice isn't answered from the cache
+    let mut data = data;
+    data.invoice.as_mut().unwrap().request = String::from("lnbc1garbage");
+    assert!(data.decoded_invoice().is_none());
+}
+
+#[tokio::test]
+async fn test_default_expiry_omitted() {
+    let body = serde_json::to_value(Charge::default()).unwrap();
+    assert!(body.get("expiresIn").is_none());
+
+    let charge = Charge {
+        expires_in: Some(600),
+        ..Default::default()
+    };
+    let body = serde_json::to_value(&charge).unwrap();
+    assert_eq!(body["expiresIn"], 600);
+}
+
+#[tokio::test]
+async fn test_wait_for_charges() {
+    let pending = r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","internalId":"","callbackUrl":"","description":"","status":"pending"}}"#;
+    let completed = r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","internalId":"","callbackUrl":"","description":"","status":"completed"}}"#;
+    let server = MockServer::start(vec![
+        MockResponse::json(200, pending),
+        MockResponse::json(200, completed),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client
+        .wait_for_charges(
+            &["c1", "c1"],
+            1,
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+        )
+        .await;
+
+    assert_eq!(r.len(), 2);
+    assert!(r.iter().all(|c| c.as_ref().unwrap().status == "completed"));
+}
+
+#[tokio::test]
+async fn test_create_charge_description_too_long() {
+    let zebedee_client = ZebedeeClient::new().build();
+    let charge = Charge {
+        amount: String::from("1000"),
+        description: "a".repeat(151),
+        ..Default::default()
+    };
+
+    let r = zebedee_client.create_charge(&charge).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Validate(_)));
+    assert!(r.to_string().contains("description"));
+}
+
+#[tokio::test]
+async fn test_create_charge_cancellation() {
+    // a server that accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hung_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let zebedee_client = ZebedeeClient::new().domain(hung_url).build();
+    let charge = Charge {
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+
+    // dropped before its first poll
+    drop(zebedee_client.create_charge(&charge));
+    // dropped while waiting on the response
+    let r = tokio::time::timeout(
+        Duration::from_millis(50),
+        zebedee_client.create_charge(&charge),
+    )
+    .await;
+    assert!(r.is_err());
+
+    let server = MockServer::start(vec![MockResponse::json(
+        201,
+        r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","internalId":"","callbackUrl":"","description":"","status":"pending"}}"#,
+    )])
+    .await;
+    let zebedee_client = zebedee_client.domain(server.url);
+    let r = zebedee_client.create_charge(&charge).await.unwrap();
+    assert!(r.success);
+}
+
+#[tokio::test]
+async fn test_get_charges_by_ids() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","status":"pending"}}"#,
+        ),
+        MockResponse::json(404, r#"{"success":false,"message":"No Charge records found with this ID."}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_charges_by_ids(&["c1", "c2"], 1).await;
+
+    assert_eq!(r[0].as_ref().unwrap().id, "c1");
+    assert!(matches!(
+        r[1],
+        Err(ZebedeeError::NotFound { ref id, ref error }) if id == "c2" && error.status == Some(404)
+    ));
+}
+
+#[tokio::test]
+async fn test_get_charge_id_is_encoded() {
+    let server = MockServer::start(vec![MockResponse::json(
+        404,
+        r#"{"success":false,"message":"No Charge records found with this ID."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let _ = zebedee_client.get_charge("../wallet?x=1#y").await;
+    assert!(zebedee_client.get_charge("..").await.is_err());
+
+    let requests = server.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with("GET /v0/charges/..%2Fwallet%3Fx=1%23y "));
+}
+
+#[tokio::test]
+async fn test_create_charge_field_errors() {
+    let server = MockServer::start(vec![MockResponse::json(
+        400,
+        r#"{"success":false,"message":"Invalid request body.","errors":[{"field":"amount","message":"amount must be a number string"}]}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let charge = Charge {
+        amount: String::from("ten"),
+        ..Default::default()
+    };
+    let r = zebedee_client.create_charge(&charge).await.err().unwrap();
+    let ZebedeeError::Api(err) = r else {
+        panic!("expected an api error, got {r:?}");
+    };
+    assert_eq!(err.errors.len(), 1);
+    assert_eq!(err.errors[0].field, "amount");
+    assert_eq!(
+        err.to_string(),
+        "Invalid request body. (amount: amount must be a number string)"
+    );
+}
+
+#[tokio::test]
+async fn test_create_charge_unstructured_errors() {
+    let server = MockServer::start(vec![MockResponse::json(
+        400,
+        r#"{"success":false,"message":"Invalid request body.","errors":["amount must be > 0"]}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let charge = Charge {
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let r = zebedee_client.create_charge(&charge).await.err().unwrap();
+    let ZebedeeError::Api(err) = r else {
+        panic!("expected an api error, got {r:?}");
+    };
+    assert!(err.errors.is_empty());
+    assert_eq!(err.to_string(), "Invalid request body.");
+}
+
+#[tokio::test]
+async fn test_verify_internal_id() {
+    let charge = ChargesData {
+        internal_id: String::from("order-1234"),
+        ..Default::default()
+    };
+    assert!(charge.verify_internal_id("order-1234").is_ok());
+    assert!(charge.verify_internal_id("").is_ok());
+
+    let charge = ChargesData {
+        internal_id: String::from("order-12"),
+        ..Default::default()
+    };
+    let r = charge.verify_internal_id("order-1234").err().unwrap();
+    assert!(matches!(r, ErrorMsg::InternalIdMismatch(_, _)));
+}
+
+#[tokio::test]
+async fn test_dry_run() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":[]}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url.clone())
+        .dry_run(true)
+        .build();
+
+    let charge = Charge {
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let r = zebedee_client.create_charge(&charge).await.unwrap();
+    assert!(r.success);
+    assert!(r.data.is_none());
+    assert!(server.requests.lock().unwrap().is_empty());
+
+    zebedee_client.get_charges(None).await.unwrap();
+    assert_eq!(server.requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_charge_log_json() {
+    let charge = Charge {
+        amount: String::from("1000"),
+        internal_id: String::from("order-1234"),
+        ..Default::default()
+    };
+
+    let log = charge.to_log_json().unwrap();
+    assert_eq!(log["internal_id"], "order-1234");
+    assert!(log.get("callback_url").is_some());
+    assert!(log.get("callbackUrl").is_none());
+}
+
+#[tokio::test]
+async fn test_charge_changed_since_last_poll() {
+    let before = ChargesData {
+        id: String::from("c1"),
+        status: ChargeStatus::Pending,
+        ..Default::default()
+    };
+    let unchanged = before.clone();
+    // decoding populates the cache but doesn't make the charge differ
+    let _ = unchanged.decoded_invoice();
+    assert_eq!(before, unchanged);
+
+    let after = ChargesData {
+        status: ChargeStatus::Completed,
+        ..before.clone()
+    };
+    assert_ne!(before, after);
+
+    // the invoice cache is interior mutable but excluded from hashing
+    #[allow(clippy::mutable_key_type)]
+    let seen: std::collections::HashSet<ChargesData> = [before, after, unchanged].into();
+    assert_eq!(seen.len(), 2);
+}
+
+#[tokio::test]
+async fn test_charges_into_map() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":[{"id":"c1","unit":"msats","amount":"1000","status":"pending"},{"id":"c2","unit":"msats","amount":"2000","status":"completed"}]}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let charges = zebedee_client.get_charges(None).await.unwrap().into_map();
+    assert_eq!(charges.len(), 2);
+    assert_eq!(charges["c2"].amount, "2000");
+}
+
+#[tokio::test]
+async fn test_malformed_timestamps() {
+    let body = r#"{"id":"c1","unit":"msats","amount":"1000","status":"pending","createdAt":"yesterday","confirmedAt":12345,"expiresAt":"2023-05-01T12:05:00.000Z"}"#;
+    let charge: ChargesData = serde_json::from_str(body).unwrap();
+    assert!(charge.created_at.is_none());
+    assert!(charge.confirmed_at.is_none());
+    assert!(charge.expires_at.is_some());
+}
+
+#[tokio::test]
+async fn test_flexible_amount_charge() {
+    let charge = Charge {
+        amount: String::from("5000"),
+        min_amount: Some(String::from("1000")),
+        max_amount: Some(String::from("100000")),
+        ..Default::default()
+    };
+    let json = serde_json::to_value(&charge).unwrap();
+    assert_eq!(json["minAmount"], "1000");
+    assert_eq!(json["maxAmount"], "100000");
+
+    let json = serde_json::to_value(Charge::default()).unwrap();
+    assert!(json.get("minAmount").is_none());
+
+    let body = r#"{"id":"c1","unit":"msats","amount":"5000","status":"pending","minAmount":"1000","maxAmount":"100000"}"#;
+    let charge: ChargesData = serde_json::from_str(body).unwrap();
+    assert_eq!(charge.min_amount.as_deref(), Some("1000"));
+}
+
+#[tokio::test]
+async fn test_get_charges_extra_params() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":[]}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let params = ListParams::default().param("status", "completed");
+    zebedee_client.get_charges(Some(&params)).await.unwrap();
+
+    let request = server.requests.lock().unwrap()[0].clone();
+    assert!(request.starts_with("GET /v0/charges?status=completed "));
+}
+
+#[tokio::test]
+async fn test_create_charge_min_amount() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":null}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url.clone()).build();
+
+    let charge = Charge {
+        amount: String::from("999"),
+        ..Default::default()
+    };
+    let r = zebedee_client.create_charge(&charge).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Validate(_)));
+    assert!(r.to_string().contains("at least 1000 msats"));
+    assert!(server.requests.lock().unwrap().is_empty());
+
+    let charge = Charge {
+        amount: String::from("1"),
+        unit: Some(UnitType::Sats),
+        ..Default::default()
+    };
+    assert!(charge.validate().is_ok());
+    let charge = Charge {
+        amount: String::from("1"),
+        unit: Some(UnitType::Usd),
+        ..Default::default()
+    };
+    assert!(charge.validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_charge_redacted() {
+    let charge = Charge {
+        amount: String::from("1000"),
+        internal_id: String::from("order-1234"),
+        callback_url: String::from("https://example.com/zbd/callback?zbd_secret=s3cr3t"),
+        ..Default::default()
+    };
+
+    let json = serde_json::to_value(charge.redacted()).unwrap();
+    assert_eq!(json["internalId"], "[redacted]");
+    assert_eq!(json["callbackUrl"], "[redacted]");
+    assert_eq!(json["amount"], "1000");
+
+    let json = serde_json::to_value(charge.redacted().fields(&["amount"])).unwrap();
+    assert_eq!(json["internalId"], "order-1234");
+    assert_eq!(json["amount"], "[redacted]");
+
+    // nested fields are masked and the view composes with snake_case logging
+    let data = ChargesData {
+        invoice: Some(InvoiceData {
+            request: String::from("lnbc1..."),
+            uri: String::from("lightning:lnbc1..."),
+        }),
+        ..Default::default()
+    };
+    let log = data.redacted().fields(&["request"]).to_log_json().unwrap();
+    assert_eq!(log["invoice"]["request"], "[redacted]");
+    assert_eq!(log["invoice"]["uri"], "lightning:lnbc1...");
+    assert!(log["confirmed_at"].is_null());
+}
+
+#[tokio::test]
+async fn test_charge_status_from_str() {
+    let status: ChargeStatus = "completed".parse().unwrap();
+    assert_eq!(status, ChargeStatus::Completed);
+    assert_eq!(status.to_string(), "completed");
+
+    // statuses this crate doesn't know yet round trip unchanged, same as through serde
+    let status: ChargeStatus = "refunded".parse().unwrap();
+    assert_eq!(status, ChargeStatus::Unknown(String::from("refunded")));
+    let from_json: ChargeStatus = serde_json::from_str(r#""refunded""#).unwrap();
+    assert_eq!(from_json, status);
+    assert_eq!(serde_json::to_string(&status).unwrap(), r#""refunded""#);
+}
+
+#[tokio::test]
+async fn test_charge_wire_format() {
+    let charge = Charge {
+        expires_in: Some(300),
+        amount: String::from("5000"),
+        description: String::from("tip"),
+        internal_id: String::from("order-1"),
+        callback_url: String::from("https://example.com/callback"),
+        unit: Some(UnitType::Msats),
+        min_amount: Some(String::from("1000")),
+        max_amount: Some(String::from("10000")),
+        callback_secret: None,
+    };
+
+    assert_eq!(
+        serde_json::to_value(&charge).unwrap(),
+        serde_json::json!({
+            "expiresIn": 300,
+            "amount": "5000",
+            "description": "tip",
+            "internalId": "order-1",
+            "callbackUrl": "https://example.com/callback",
+            "unit": "msats",
+            "minAmount": "1000",
+            "maxAmount": "10000",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_charges_sum_by_status() {
+    let body = r#"{"success":true,"data":[
+        {"id":"c1","unit":"msats","amount":"1000","status":"pending"},
+        {"id":"c2","unit":"msats","amount":"2000","status":"completed"},
+        {"id":"c3","unit":"sats","amount":"3","status":"completed"},
+        {"id":"c4","unit":"usd","amount":"500","status":"completed"},
+        {"id":"c5","unit":"msats","amount":"oops","status":"pending"}
+    ]}"#;
+    let charges: FetchChargesResponse = serde_json::from_str(body).unwrap();
+
+    let sums = charges.sum_by_status();
+    assert_eq!(sums.len(), 2);
+    assert_eq!(sums[&ChargeStatus::Pending], 1000);
+    assert_eq!(sums[&ChargeStatus::Completed], 5000);
+}
+
+#[tokio::test]
+async fn test_format_amount() {
+    assert_eq!(format_amount("1234000", &UnitType::Msats), "1,234 sats");
+    assert_eq!(format_amount("1000", &UnitType::Msats), "1 sat");
+    assert_eq!(format_amount("1500", &UnitType::Msats), "1.5 sats");
+    assert_eq!(format_amount("21", &UnitType::Msats), "0.021 sats");
+    assert_eq!(
+        format_amount("100000000", &UnitType::Sats),
+        "100,000,000 sats"
+    );
+    assert_eq!(format_amount("1234", &UnitType::Usd), "$12.34");
+    assert_eq!(format_amount("123456705", &UnitType::Usd), "$1,234,567.05");
+    assert_eq!(format_amount("12.5", &UnitType::Usd), "12.5");
+    assert_eq!(format_amount("n/a", &UnitType::Usd), "n/a");
+}
+
+#[tokio::test]
+async fn test_poll_charge_stream() {
+    let charge = |status: &str| {
+        format!(
+            r#"{{"success":true,"data":{{"id":"c1","unit":"msats","amount":"1000","status":"{status}"}}}}"#
+        )
+    };
+    let server = MockServer::start(vec![
+        MockResponse::json(200, &charge("pending")),
+        MockResponse::json(200, &charge("pending")),
+        MockResponse::json(200, &charge("completed")),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url.clone()).build();
+
+    let statuses: Vec<ChargeStatus> = zebedee_client
+        .poll_charge_stream("c1", Duration::from_millis(10))
+        .map(Result::unwrap)
+        .collect()
+        .await;
+    assert_eq!(
+        statuses,
+        vec![ChargeStatus::Pending, ChargeStatus::Completed]
+    );
+    assert_eq!(server.requests.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_parse_amount() {
+    assert_eq!(parse_amount("1,234,000", &UnitType::Msats), Some(1_234_000));
+    assert_eq!(parse_amount("1000.000", &UnitType::Msats), Some(1000));
+    assert_eq!(parse_amount("1000.5", &UnitType::Msats), None);
+    assert_eq!(parse_amount("1,234", &UnitType::Usd), Some(1234));
+    assert_eq!(parse_amount("12", &UnitType::Usd), Some(12));
+    assert_eq!(parse_amount("12.00", &UnitType::Usd), None);
+    assert_eq!(parse_amount("$12", &UnitType::Usd), None);
+    assert_eq!(parse_amount("-5", &UnitType::Sats), None);
+    assert_eq!(parse_amount("", &UnitType::Sats), None);
+
+    let body = r#"{"id":"c1","unit":"usd","amount":"1250","status":"pending"}"#;
+    let charge: ChargesData = serde_json::from_str(body).unwrap();
+    assert_eq!(charge.usd_amount(), Some(UsdAmount(1250)));
+}
+
+#[tokio::test]
+async fn test_create_checkout() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"5000","status":"pending","expiresAt":"2023-05-01T12:05:00.000Z","invoice":{"request":"lnbc50n1abc","uri":"lightning:lnbc50n1abc"}}}"#,
+        ),
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"id":"c2","unit":"msats","amount":"5000","status":"pending"}}"#,
+        ),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+    let charge = Charge {
+        amount: String::from("5000"),
+        ..Default::default()
+    };
+
+    let checkout = zebedee_client.create_checkout(&charge).await.unwrap();
+    assert_eq!(checkout.charge_id, "c1");
+    assert_eq!(checkout.invoice, "lnbc50n1abc");
+    assert_eq!(checkout.qr_data(), "LIGHTNING:LNBC50N1ABC");
+    assert!(checkout.expires_at.is_some());
+
+    let r = zebedee_client.create_checkout(&charge).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::MissingData(_))));
+}
+
+#[tokio::test]
+async fn test_charge_amount_with_unit() {
+    let body = r#"{"id":"c1","unit":"sats","amount":"1,500","status":"pending"}"#;
+    let charge: ChargesData = serde_json::from_str(body).unwrap();
+    let amount = charge.amount().unwrap();
+    assert_eq!(
+        amount,
+        Amount {
+            value: 1500,
+            unit: UnitType::Sats
+        }
+    );
+    assert_eq!(amount.to_msats(), Some(1_500_000));
+    assert_eq!(amount.to_string(), "1,500 sats");
+
+    let usd = Amount::parse("1234", "usd").unwrap();
+    assert_eq!(usd.value, 1234);
+    assert_eq!(usd.to_msats(), None);
+    assert_eq!(Amount::parse("12.34", "usd"), None);
+    assert_eq!(Amount::parse("1000", "btc"), None);
+}
+
+#[tokio::test]
+async fn test_wait_for_charge_cancelled() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","status":"pending"}}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let canceller = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+
+    let started = std::time::Instant::now();
+    let r = zebedee_client
+        .wait_for_charge(
+            "c1",
+            Duration::from_secs(10),
+            Duration::from_secs(60),
+            Some(&cancel),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::Cancelled(ref id)) if id == "c1"));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_list_charges() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":[{"id":"c1","unit":"msats","amount":"1000","status":"pending"}]}"#,
+        ),
+        MockResponse::json(200, r#"{"success":true,"data":null}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let charges = zebedee_client.list_charges(None).await.unwrap();
+    assert_eq!(charges.len(), 1);
+    assert_eq!(charges[0].id, "c1");
+
+    assert!(zebedee_client.list_charges(None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_iterate_charges_response() {
+    let body = r#"{"success":true,"data":[{"id":"c1","unit":"msats","amount":"1000","status":"pending"},{"id":"c2","unit":"msats","amount":"2000","status":"completed"}]}"#;
+    let charges: FetchChargesResponse = serde_json::from_str(body).unwrap();
+
+    let ids: Vec<&str> = charges.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, ["c1", "c2"]);
+    let mut total = 0;
+    for charge in &charges {
+        total += charge.amount.parse::<u64>().unwrap();
+    }
+    assert_eq!(total, 3000);
+    assert_eq!(charges.into_iter().count(), 2);
+
+    let empty: FetchChargesResponse =
+        serde_json::from_str(r#"{"success":true,"data":null}"#).unwrap();
+    assert_eq!(empty.iter().count(), 0);
+}
+
+#[tokio::test]
+async fn test_deserialize_error() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","status":"pending","invoice":{"request":42,"uri":"lightning:lnbc1"}}}"#,
+        ),
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":[{"id":"c1","unit":"msats","amount":"1000","status":"pending"},{"id":"c2","unit":"msats","status":"pending"}]}"#,
+        ),
+        MockResponse::json(200, r#"{"success":true,"data":[{"id":"c1""#),
+        MockResponse::json(200, r#"{"success":false,"message":"Charge expired."}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_charge("c1").await.err().unwrap();
+    assert!(
+        matches!(r, ZebedeeError::Deserialize { ref path, ref body, .. } if path == "data.invoice.request" && body.contains("\"request\":42"))
+    );
+    assert!(r
+        .to_string()
+        .starts_with("Unable to parse json at `data.invoice.request`: invalid type"));
+
+    let r = zebedee_client.get_charges(None).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Deserialize { ref path, .. } if path == "data[1]"));
+    assert!(r.to_string().contains("missing field `amount`"));
+
+    let r = zebedee_client.get_charges(None).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::InvalidJson(_)));
+
+    let r = zebedee_client.get_charge("c1").await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Api(ref error) if error.message == "Charge expired."));
+}
+
+#[tokio::test]
+async fn test_poll_budget() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"id":"c1","unit":"msats","amount":"1000","status":"pending"}}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let budget = PollBudget::new(Duration::from_millis(100));
+    assert_eq!(
+        budget.limit(Duration::from_millis(10)),
+        Duration::from_millis(10)
+    );
+
+    let started = std::time::Instant::now();
+    let r = zebedee_client
+        .wait_for_charge(
+            "c1",
+            Duration::from_millis(10),
+            budget.limit(Duration::from_secs(60)),
+            None,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::PollTimeout(_))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(budget.is_exhausted());
+
+    // a later wait drawing from the spent budget gives up without polling
+    let polled = server.requests.lock().unwrap().len();
+    let r = zebedee_client
+        .wait_for_charge(
+            "c2",
+            Duration::from_millis(10),
+            budget.limit(Duration::from_secs(60)),
+            None,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::PollTimeout(ref id)) if id == "c2"));
+    assert_eq!(server.requests.lock().unwrap().len(), polled);
+}
+
+#[tokio::test]
+async fn test_generated_internal_id() {
+    let first = Charge::with_generated_internal_id();
+    let second = Charge::with_generated_internal_id();
+    assert_ne!(first.internal_id, second.internal_id);
+
+    let id = &first.internal_id;
+    assert_eq!(id.len(), 36);
+    let groups: Vec<&str> = id.split('-').collect();
+    assert_eq!(
+        groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+        [8, 4, 4, 4, 12]
+    );
+    assert!(groups[2].starts_with('4'));
+    assert!(matches!(
+        groups[3].chars().next(),
+        Some('8' | '9' | 'a' | 'b')
+    ));
+    assert!(id
+        .chars()
+        .all(|c| c == '-' || c.is_ascii_digit() || ('a'..='f').contains(&c)));
+    assert_eq!(first.amount, Charge::default().amount);
+}
+
+#[tokio::test]
+async fn test_get_charges_pages() {
+    let page = |ids: &[&str]| {
+        let charges: Vec<String> = ids
+            .iter()
+            .map(|id| {
+                format!(r#"{{"id":"{id}","unit":"msats","amount":"1000","status":"pending"}}"#)
+            })
+            .collect();
+        format!(r#"{{"success":true,"data":[{}]}}"#, charges.join(","))
+    };
+    let server = MockServer::start(vec![
+        MockResponse::json(200, &page(&["c1", "c2"])),
+        MockResponse::json(200, &page(&["c3", "c4"])),
+        MockResponse::json(200, &page(&["c5"])),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let pages: Vec<_> = zebedee_client
+        .get_charges_pages(ListParams::default(), 2, 2)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(|page| page.unwrap().len())
+        .collect();
+    assert_eq!(pages, [2, 2, 1]);
+
+    let requests = server.requests.lock().unwrap().clone();
+    for page in 1..=3 {
+        let query = format!("page={page}&limit=2");
+        assert!(requests.iter().any(|r| r.contains(&query)));
+    }
+
+    // An API that ignores `page` keeps returning the same full page.
+    let server = MockServer::start(vec![MockResponse::json(200, &page(&["c1", "c2"]))]).await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+    let pages: Vec<_> = zebedee_client
+        .get_charges_pages(ListParams::default(), 2, 1)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(pages.len(), 1);
+}
+
+#[cfg(feature = "utilities")]
+#[tokio::test]
+async fn test_charge_with_usd() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"btcUsdPrice":"50000","btcUsdTimestamp":"1700000000"}}"#,
+        ),
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"btcUsdPrice":"0","btcUsdTimestamp":"1700000000"}}"#,
+        ),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    // $5 at $50,000 per BTC is 0.0001 BTC, 10,000 sats
+    let charge = Charge::with_usd(5.0, &zebedee_client).await.unwrap();
+    assert_eq!(charge.amount, "10000000");
+    assert_eq!(charge.unit, Some(UnitType::Msats));
+
+    let r = Charge::with_usd(5.0, &zebedee_client).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::BadPayloadData(_))));
+    assert!(Charge::with_usd(-1.0, &zebedee_client).await.is_err());
+}
+
+#[tokio::test]
+async fn test_typed_errors_convert_to_anyhow() {
+    let server = MockServer::start(vec![MockResponse::json(
+        404,
+        r#"{"success":false,"message":"No Charge records found with this ID."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let fetch = || async {
+        let charge = zebedee_client.get_charge("c9").await?;
+        anyhow::Ok(charge)
+    };
+    let r = fetch().await.err().unwrap();
+    assert!(r.to_string().contains("No Charge records"));
+    assert!(matches!(
+        r.downcast_ref::<ZebedeeError>(),
+        Some(ZebedeeError::NotFound { id, .. }) if id == "c9"
+    ));
+}
+
+#[tokio::test]
+async fn test_msats() {
+    assert_eq!(Msats::from_sats(10), Msats(10_000));
+    assert_eq!(Msats::from_btc(0.0001), Msats(10_000_000));
+    assert_eq!(Msats::from_btc(-1.0), Msats(0));
+    assert_eq!(serde_json::to_string(&Msats(1500)).unwrap(), r#""1500""#);
+    assert_eq!(Msats::from_sats(1234).to_string(), "1,234 sats");
+
+    let charge = Charge::msats(Msats::from_sats(10));
+    assert_eq!(charge.amount, "10000");
+    assert_eq!(charge.unit, Some(UnitType::Msats));
+    assert_eq!(Charge::msats(2500).amount, "2500");
+    assert!(charge.validate().is_ok());
+}
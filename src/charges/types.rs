@@ -1,57 +1,316 @@
-use crate::StdResp;
+use crate::{
+    bolt11::{self, DecodedInvoice},
+    custom_deserializer::deserialize_lenient_datetime,
+    errors::ErrorMsg,
+    models::{
+        generate_internal_id, parse_amount, verify_internal_id, Amount, ChargeStatus, Msats,
+        UnitType, UsdAmount,
+    },
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::OnceLock};
+use validator::{Validate, ValidationError};
 
 pub type FetchChargesResponse = StdResp<Option<Vec<ChargesData>>>;
 pub type FetchOneChargeResponse = StdResp<Option<ChargesData>>;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct InvoiceData {
     pub request: String,
     pub uri: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct ChargesData {
     pub id: String,
     pub unit: String,
     pub amount: String,
-    #[serde(rename = "createdAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub created_at: Option<DateTime<Utc>>,
-    #[serde(rename = "internalId")]
+    #[serde(default)]
     pub internal_id: String,
-    #[serde(rename = "callbackUrl")]
-    pub callback_url: String,
+    pub callback_url: Option<String>,
+    #[serde(default)]
     pub description: String,
-    #[serde(rename = "expiresAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub expires_at: Option<DateTime<Utc>>,
-    #[serde(rename = "confirmedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub confirmed_at: Option<DateTime<Utc>>,
-    pub status: String,
+    pub status: ChargeStatus,
+    /// Lowest amount the payer may choose, for flexible-amount charges
+    pub min_amount: Option<String>,
+    /// Highest amount the payer may choose, for flexible-amount charges
+    pub max_amount: Option<String>,
     pub invoice: Option<InvoiceData>,
+    #[serde(skip)]
+    pub(crate) invoice_cache: InvoiceCache,
+}
+
+/// Memoized result of [`ChargesData::decoded_invoice`], with the request it was decoded from so
+/// a changed `invoice` is decoded again.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InvoiceCache(OnceLock<(String, Option<DecodedInvoice>)>);
+
+// The cache only holds data derived from the invoice, so it never affects equality or hashing.
+impl PartialEq for InvoiceCache {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for InvoiceCache {}
+
+impl std::hash::Hash for InvoiceCache {
+    fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+}
+
+impl FetchChargesResponse {
+    /// Collects the listed charges into a map keyed by charge id.
+    pub fn into_map(self) -> HashMap<String, ChargesData> {
+        self.data
+            .unwrap_or_default()
+            .into_iter()
+            .map(|charge| (charge.id.clone(), charge))
+            .collect()
+    }
+
+    /// Totals the listed charge amounts in msats per status, e.g. for "total pending" on a
+    /// dashboard. USD charges and amounts that don't parse are left out.
+    pub fn sum_by_status(&self) -> HashMap<ChargeStatus, u64> {
+        let mut sums = HashMap::new();
+        for charge in self.data.iter().flatten() {
+            let Some(msats) = charge.amount().and_then(|amount| amount.to_msats()) else {
+                continue;
+            };
+            let sum = sums.entry(charge.status.clone()).or_insert(0u64);
+            *sum = sum.saturating_add(msats);
+        }
+        sums
+    }
+}
+
+/// Transaction that settled a Charge.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
+pub struct ChargeTransaction {
+    pub id: String,
+    pub wallet_id: Option<String>,
+    pub r#type: Option<String>,
+    pub total_amount: Option<String>,
+    pub fee: String,
+    pub amount: String,
+    pub description: Option<String>,
+    pub status: String,
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
+    pub confirmed_at: Option<DateTime<Utc>>,
 }
 
+impl ChargesData {
+    /// Decodes the charge's BOLT11 invoice, memoizing the result so polling the same charge
+    /// doesn't decode it again. Returns `None` when the charge has no invoice or it can't be
+    /// decoded.
+    pub fn decoded_invoice(&self) -> Option<DecodedInvoice> {
+        let request = &self.invoice.as_ref()?.request;
+        let (cached, decoded) = self
+            .invoice_cache
+            .0
+            .get_or_init(|| (request.clone(), bolt11::decode(request).ok()));
+        if cached == request {
+            decoded.clone()
+        } else {
+            bolt11::decode(request).ok()
+        }
+    }
+
+    /// The charge amount with its unit, `None` when either can't be parsed.
+    pub fn amount(&self) -> Option<Amount> {
+        Amount::parse(&self.amount, &self.unit)
+    }
+
+    /// Interprets the charge amount as US cents when the charge was created in USD.
+    /// Returns `None` for sats-denominated charges or when the amount can't be parsed.
+    pub fn usd_amount(&self) -> Option<UsdAmount> {
+        if self.unit != "usd" {
+            return None;
+        }
+        parse_amount(&self.amount, &UnitType::Usd).map(UsdAmount)
+    }
+
+    /// Checks that the created charge carries the `internal_id` it was created with,
+    /// catching ids dropped or truncated by the API before they break reconciliation.
+    pub fn verify_internal_id(&self, sent: &str) -> Result<(), ErrorMsg> {
+        verify_internal_id(sent, Some(&self.internal_id))
+    }
+}
+
+/// Everything needed to render a payment screen for a newly created Charge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkout {
+    pub charge_id: String,
+    /// BOLT11 invoice for wallets that take it pasted
+    pub invoice: String,
+    /// `lightning:` URI, for links that open the payer's wallet
+    pub uri: String,
+    pub amount: String,
+    pub unit: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl Checkout {
+    /// Contents for the payment QR code: the `lightning:` URI, uppercased so QR encoders can
+    /// use the denser alphanumeric mode. Pass it to the QR library of your choice.
+    pub fn qr_data(&self) -> String {
+        self.uri.to_uppercase()
+    }
+}
+
+/// Fails with `MissingData` when the charge came back without an invoice.
+impl TryFrom<ChargesData> for Checkout {
+    type Error = ErrorMsg;
+
+    fn try_from(value: ChargesData) -> Result<Self, Self::Error> {
+        let invoice = value
+            .invoice
+            .ok_or_else(|| ErrorMsg::MissingData(format!("invoice of charge {}", value.id)))?;
+        Ok(Checkout {
+            charge_id: value.id,
+            invoice: invoice.request,
+            uri: invoice.uri,
+            amount: value.amount,
+            unit: value.unit,
+            expires_at: value.expires_at,
+        })
+    }
+}
+
+/// Smallest charge the API accepts, 1 sat.
+pub const MIN_CHARGE_MSATS: u64 = 1000;
+
+/// Most pages [`ZebedeeClient::get_charges_pages`](crate::ZebedeeClient::get_charges_pages)
+/// requests before ending the stream.
+pub const MAX_CHARGE_PAGES: u32 = 1000;
+
 /// Use this struct to create a well crafted json body for your charge requests
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+#[validate(schema(function = "validate_min_amount"))]
 pub struct Charge {
-    #[serde(rename = "expiresIn")]
-    pub expires_in: u32,
+    /// Seconds the charge stays payable, left out of the request when `None` so the API's
+    /// default expiry applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u32>,
     pub amount: String,
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub description: String,
-    #[serde(rename = "internalId")]
     pub internal_id: String,
-    #[serde(rename = "callbackUrl")]
     pub callback_url: String,
+    /// Unit of `amount`, omitted to use the API default of millisatoshis.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<UnitType>,
+    /// Lets the payer choose an amount from this one up to `max_amount`, with `amount` as the
+    /// suggestion. Useful for tips.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<String>,
+    /// Secret appended to `callback_url` by `create_charge`, for checking this charge's
+    /// callbacks with [`parse_webhook_with`](crate::callbacks::parse_webhook_with).
+    /// Never sent on its own.
+    #[serde(skip)]
+    pub callback_secret: Option<String>,
+}
+
+impl Charge {
+    /// Creates a charge for `amount` msats, e.g. `Charge::msats(Msats::from_sats(10))` for
+    /// 10 sats, so the unit can't be mistaken.
+    pub fn msats(amount: impl Into<Msats>) -> Self {
+        Charge {
+            amount: amount.into().0.to_string(),
+            unit: Some(UnitType::Msats),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a USD-denominated charge, where `amount` is sent as a number of cents.
+    pub fn usd(amount: UsdAmount) -> Self {
+        Charge {
+            amount: amount.cents().to_string(),
+            unit: Some(UnitType::Usd),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a charge for `dollars` worth of msats at the current BTC/USD rate from
+    /// [`ZebedeeClient::get_btc_usd`](crate::ZebedeeClient::get_btc_usd), so the invoice
+    /// is for a fixed amount of sats rather than a USD-denominated charge.
+    #[cfg(feature = "utilities")]
+    pub async fn with_usd(dollars: f64, client: &crate::ZebedeeClient) -> crate::Result<Self> {
+        if !dollars.is_finite() || dollars <= 0.0 {
+            return Err(ErrorMsg::BadPayloadData(format!("invalid USD amount {dollars}")).into());
+        }
+        let price = client
+            .get_btc_usd()
+            .await?
+            .data
+            .ok_or_else(|| ErrorMsg::MissingData(String::from(crate::endpoints::BTCUSD)))?
+            .btc_usd_price;
+        let btc_usd = price
+            .parse::<f64>()
+            .ok()
+            .filter(|price| price.is_finite() && *price > 0.0)
+            .ok_or_else(|| ErrorMsg::BadPayloadData(format!("invalid BTC/USD price {price}")))?;
+
+        Ok(Charge::msats(Msats::from_btc(dollars / btc_usd)))
+    }
+
+    /// Creates a charge with a random UUID `internal_id`, for correlating it later when you
+    /// don't have ids of your own. Store `internal_id` before creating the charge.
+    pub fn with_generated_internal_id() -> Self {
+        Charge {
+            internal_id: generate_internal_id(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Rejects msat and sat amounts below [`MIN_CHARGE_MSATS`]. USD amounts and amounts that
+/// don't parse are left for the API to judge.
+fn validate_min_amount(charge: &Charge) -> Result<(), ValidationError> {
+    let Ok(amount) = charge.amount.parse::<u64>() else {
+        return Ok(());
+    };
+    let msats = match charge.unit {
+        None | Some(UnitType::Msats) => amount,
+        Some(UnitType::Sats) => amount.saturating_mul(1000),
+        Some(UnitType::Usd) => return Ok(()),
+    };
+    if msats < MIN_CHARGE_MSATS {
+        let mut err = ValidationError::new("min_amount");
+        err.message =
+            Some(format!("amount must be at least {MIN_CHARGE_MSATS} msats (1 sat)").into());
+        return Err(err);
+    }
+    Ok(())
 }
 
 impl Default for Charge {
     fn default() -> Self {
         Charge {
-            expires_in: 300,
+            expires_in: None,
             amount: String::from("0"),
             description: String::from("using zebedee rust sdk"),
             internal_id: String::from(""),
             callback_url: String::from(""),
+            unit: None,
+            min_amount: None,
+            max_amount: None,
+            callback_secret: None,
         }
     }
 }
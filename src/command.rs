@@ -0,0 +1,159 @@
+#[cfg(feature = "charges")]
+use crate::charges::Charge;
+#[cfg(feature = "email")]
+use crate::email::EmailPaymentReqest;
+#[cfg(feature = "gamertag")]
+use crate::gamertag::GamertagPayment;
+#[cfg(feature = "internal_transfer")]
+use crate::internal_transfer::InternalTransfer;
+#[cfg(feature = "keysend")]
+use crate::keysend::Keysend;
+#[cfg(feature = "ln_address")]
+use crate::ln_address::*;
+#[cfg(any(feature = "charges", feature = "withdrawal_request"))]
+use crate::models::ListParams;
+#[cfg(feature = "payments")]
+use crate::payments::Payment;
+#[cfg(feature = "withdrawal_request")]
+use crate::withdrawal_request::WithdrawalReqest;
+use crate::{Result, ZebedeeClient};
+use serde_json::Value;
+
+/// A single ZEBEDEE API call, for scripting tools and admin consoles that choose the
+/// operation at runtime. Run it with [`ZebedeeClient::execute`].
+#[derive(Debug)]
+pub enum Command {
+    GetWallet,
+    #[cfg(feature = "keysend")]
+    Keysend(Keysend),
+    #[cfg(feature = "charges")]
+    CreateCharge(Charge),
+    #[cfg(feature = "charges")]
+    GetCharges(Option<ListParams>),
+    #[cfg(feature = "charges")]
+    GetCharge(String),
+    #[cfg(feature = "gamertag")]
+    PayGamertag(GamertagPayment),
+    #[cfg(feature = "gamertag")]
+    FetchChargeFromGamertag(GamertagPayment),
+    #[cfg(feature = "gamertag")]
+    GetGamertagTx(String),
+    #[cfg(feature = "gamertag")]
+    GetUseridByGamertag(String),
+    #[cfg(feature = "gamertag")]
+    GetGamertagByUserid(String),
+    #[cfg(feature = "internal_transfer")]
+    InternalTransfer(InternalTransfer),
+    #[cfg(feature = "ln_address")]
+    PayLnAddress(LnPayment),
+    #[cfg(feature = "ln_address")]
+    FetchChargeLnAddress(LnFetchCharge),
+    #[cfg(feature = "ln_address")]
+    ValidateLnAddress(LnAddress),
+    #[cfg(feature = "payments")]
+    PayInvoice(Payment),
+    #[cfg(feature = "payments")]
+    GetPayments,
+    #[cfg(feature = "payments")]
+    GetPayment(String),
+    #[cfg(feature = "utilities")]
+    GetIsSupportedRegionByIp(String),
+    #[cfg(feature = "utilities")]
+    GetProdIps,
+    #[cfg(feature = "utilities")]
+    GetBtcUsd,
+    #[cfg(feature = "withdrawal_request")]
+    CreateWithdrawalRequest(WithdrawalReqest),
+    #[cfg(feature = "withdrawal_request")]
+    GetWithdrawalRequests(Option<ListParams>),
+    #[cfg(feature = "withdrawal_request")]
+    GetWithdrawalRequest(String),
+    #[cfg(feature = "email")]
+    PayEmail(EmailPaymentReqest),
+}
+
+impl ZebedeeClient {
+    /// Runs `command` and returns the full API response as generic JSON.
+    pub async fn execute(&self, command: Command) -> Result<Value> {
+        let value = match command {
+            Command::GetWallet => serde_json::to_value(self.get_wallet_details().await?)?,
+            #[cfg(feature = "keysend")]
+            Command::Keysend(payload) => serde_json::to_value(self.keysend(&payload).await?)?,
+            #[cfg(feature = "charges")]
+            Command::CreateCharge(charge) => {
+                serde_json::to_value(self.create_charge(&charge).await?)?
+            }
+            #[cfg(feature = "charges")]
+            Command::GetCharges(params) => {
+                serde_json::to_value(self.get_charges(params.as_ref()).await?)?
+            }
+            #[cfg(feature = "charges")]
+            Command::GetCharge(id) => serde_json::to_value(self.get_charge(id).await?)?,
+            #[cfg(feature = "gamertag")]
+            Command::PayGamertag(payment) => {
+                serde_json::to_value(self.pay_gamertag(&payment).await?)?
+            }
+            #[cfg(feature = "gamertag")]
+            Command::FetchChargeFromGamertag(payment) => {
+                serde_json::to_value(self.fetch_charge_from_gamertag(&payment).await?)?
+            }
+            #[cfg(feature = "gamertag")]
+            Command::GetGamertagTx(id) => serde_json::to_value(self.get_gamertag_tx(id).await?)?,
+            #[cfg(feature = "gamertag")]
+            Command::GetUseridByGamertag(gamertag) => {
+                serde_json::to_value(self.get_userid_by_gamertag(gamertag).await?)?
+            }
+            #[cfg(feature = "gamertag")]
+            Command::GetGamertagByUserid(user_id) => {
+                serde_json::to_value(self.get_gamertag_by_userid(user_id).await?)?
+            }
+            #[cfg(feature = "internal_transfer")]
+            Command::InternalTransfer(payload) => {
+                serde_json::to_value(self.internal_transfer(&payload).await?)?
+            }
+            #[cfg(feature = "ln_address")]
+            Command::PayLnAddress(payment) => {
+                serde_json::to_value(self.pay_ln_address(&payment).await?)?
+            }
+            #[cfg(feature = "ln_address")]
+            Command::FetchChargeLnAddress(payment) => {
+                serde_json::to_value(self.fetch_charge_ln_address(&payment).await?)?
+            }
+            #[cfg(feature = "ln_address")]
+            Command::ValidateLnAddress(address) => {
+                serde_json::to_value(self.validate_ln_address(&address).await?)?
+            }
+            #[cfg(feature = "payments")]
+            Command::PayInvoice(payment) => {
+                serde_json::to_value(self.pay_invoice(&payment).await?)?
+            }
+            #[cfg(feature = "payments")]
+            Command::GetPayments => serde_json::to_value(self.get_payments().await?)?,
+            #[cfg(feature = "payments")]
+            Command::GetPayment(id) => serde_json::to_value(self.get_payment(id).await?)?,
+            #[cfg(feature = "utilities")]
+            Command::GetIsSupportedRegionByIp(ip) => {
+                serde_json::to_value(self.get_is_supported_region_by_ip(ip).await?)?
+            }
+            #[cfg(feature = "utilities")]
+            Command::GetProdIps => serde_json::to_value(self.get_prod_ips().await?)?,
+            #[cfg(feature = "utilities")]
+            Command::GetBtcUsd => serde_json::to_value(self.get_btc_usd().await?)?,
+            #[cfg(feature = "withdrawal_request")]
+            Command::CreateWithdrawalRequest(withdrawal_request) => {
+                serde_json::to_value(self.create_withdrawal_request(&withdrawal_request).await?)?
+            }
+            #[cfg(feature = "withdrawal_request")]
+            Command::GetWithdrawalRequests(params) => {
+                serde_json::to_value(self.get_withdrawal_requests(params.as_ref()).await?)?
+            }
+            #[cfg(feature = "withdrawal_request")]
+            Command::GetWithdrawalRequest(id) => {
+                serde_json::to_value(self.get_withdrawal_request(id).await?)?
+            }
+            #[cfg(feature = "email")]
+            Command::PayEmail(payment) => serde_json::to_value(self.pay_email(&payment).await?)?,
+        };
+        Ok(value)
+    }
+}
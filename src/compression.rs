@@ -0,0 +1,390 @@
+//! Decoding of `gzip` and `deflate` response bodies (RFC 1950, 1951 and 1952). reqwest's own
+//! decompression features aren't enabled, so bodies arrive as sent and are decoded here, with
+//! the decoded size checked against `max_body_size` as it grows.
+
+use crate::errors::ErrorMsg;
+
+/// Value sent as `Accept-Encoding`, the encodings [`decode`] understands.
+pub(crate) const ACCEPTED_ENCODINGS: &str = "gzip, deflate";
+
+/// Decodes `body` sent with the given `Content-Encoding`. Fails with `BodyTooLarge` as soon as
+/// the decoded body would exceed `limit`.
+pub(crate) fn decode(encoding: &str, body: &[u8], limit: Option<usize>) -> crate::Result<Vec<u8>> {
+    let limit = limit.unwrap_or(usize::MAX);
+    let decoded = match encoding {
+        "gzip" | "x-gzip" => gunzip(body, limit),
+        "deflate" => zlib_inflate(body, limit),
+        _ => Err(Error::Corrupt("unsupported content encoding")),
+    };
+    decoded.map_err(|e| {
+        match e {
+            Error::TooLarge => ErrorMsg::BodyTooLarge(limit),
+            Error::Corrupt(reason) => {
+                ErrorMsg::BadContentEncoding(encoding.to_string(), reason.to_string())
+            }
+        }
+        .into()
+    })
+}
+
+#[derive(Debug)]
+enum Error {
+    Corrupt(&'static str),
+    TooLarge,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Decodes each gzip member in turn; concatenated members make up one body.
+fn gunzip(mut data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let header = gzip_header_len(data)?;
+        let start = out.len();
+        let used = inflate(&data[header..], &mut out, limit)?;
+        let trailer = data
+            .get(header + used..header + used + 8)
+            .ok_or(Error::Corrupt("truncated gzip trailer"))?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc != crc32(&out[start..]) || size != (out.len() - start) as u32 {
+            return Err(Error::Corrupt("gzip checksum mismatch"));
+        }
+        data = &data[header + used + 8..];
+        if data.is_empty() {
+            return Ok(out);
+        }
+    }
+}
+
+fn gzip_header_len(data: &[u8]) -> Result<usize> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+
+    let truncated = Error::Corrupt("truncated gzip header");
+    if data.len() < 10 {
+        return Err(truncated);
+    }
+    if data[..3] != [0x1f, 0x8b, 8] {
+        return Err(Error::Corrupt("not a gzip stream"));
+    }
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = data.get(pos..pos + 2).ok_or(truncated)?;
+        pos += 2 + usize::from(u16::from_le_bytes([len[0], len[1]]));
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = data
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+                .ok_or(Error::Corrupt("truncated gzip header"))?;
+            pos += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    if pos > data.len() {
+        return Err(Error::Corrupt("truncated gzip header"));
+    }
+    Ok(pos)
+}
+
+/// HTTP's `deflate` is meant to be zlib wrapped, but some servers send a raw deflate stream, so
+/// that is accepted too.
+fn zlib_inflate(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let is_zlib = data.len() >= 2
+        && data[0] & 0x0f == 8
+        && data[1] & 0x20 == 0
+        && (u16::from(data[0]) << 8 | u16::from(data[1])) % 31 == 0;
+    if !is_zlib {
+        inflate(data, &mut out, limit)?;
+        return Ok(out);
+    }
+
+    let used = inflate(&data[2..], &mut out, limit)?;
+    let checksum = data
+        .get(2 + used..2 + used + 4)
+        .ok_or(Error::Corrupt("truncated zlib trailer"))?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&out) {
+        return Err(Error::Corrupt("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
+
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    len: u32,
+}
+
+impl Bits<'_> {
+    fn take(&mut self, n: u32) -> Result<u32> {
+        while self.len < n {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or(Error::Corrupt("truncated deflate stream"))?;
+            self.pos += 1;
+            self.buf |= u32::from(byte) << self.len;
+            self.len += 8;
+        }
+        let value = self.buf & ((1 << n) - 1);
+        self.buf >>= n;
+        self.len -= n;
+        Ok(value)
+    }
+
+    /// Drops the rest of the current byte, bytes are only ever read as far as needed.
+    fn align(&mut self) {
+        self.buf = 0;
+        self.len = 0;
+    }
+}
+
+/// Canonical Huffman code, as symbols ordered by code length.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+        counts[0] = 0;
+
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(Error::Corrupt("over-subscribed huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[usize::from(offsets[usize::from(len)])] = symbol as u16;
+                offsets[usize::from(len)] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.take(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::Corrupt("invalid huffman code"))
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order in which code length code lengths are sent in a dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// Inflates a raw deflate stream onto `out`, returning how many input bytes it took up.
+fn inflate(data: &[u8], out: &mut Vec<u8>, limit: usize) -> Result<usize> {
+    let mut bits = Bits {
+        data,
+        pos: 0,
+        buf: 0,
+        len: 0,
+    };
+    loop {
+        let last = bits.take(1)? == 1;
+        match bits.take(2)? {
+            0 => stored_block(&mut bits, out, limit)?,
+            1 => {
+                let (literals, distances) = fixed_codes()?;
+                compressed_block(&mut bits, out, limit, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                compressed_block(&mut bits, out, limit, &literals, &distances)?;
+            }
+            _ => return Err(Error::Corrupt("invalid deflate block type")),
+        }
+        if last {
+            return Ok(bits.pos);
+        }
+    }
+}
+
+fn stored_block(bits: &mut Bits, out: &mut Vec<u8>, limit: usize) -> Result<()> {
+    bits.align();
+    let header = bits
+        .data
+        .get(bits.pos..bits.pos + 4)
+        .ok_or(Error::Corrupt("truncated stored block"))?;
+    let len = usize::from(u16::from_le_bytes([header[0], header[1]]));
+    if u16::from_le_bytes([header[2], header[3]]) != !(len as u16) {
+        return Err(Error::Corrupt("stored block length mismatch"));
+    }
+    bits.pos += 4;
+    let block = bits
+        .data
+        .get(bits.pos..bits.pos + len)
+        .ok_or(Error::Corrupt("truncated stored block"))?;
+    if out.len() + len > limit {
+        return Err(Error::TooLarge);
+    }
+    out.extend_from_slice(block);
+    bits.pos += len;
+    Ok(())
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman)> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman)> {
+    let literal_count = bits.take(5)? as usize + 257;
+    let distance_count = bits.take(5)? as usize + 1;
+    let code_length_count = bits.take(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(Error::Corrupt("too many huffman codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = bits.take(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; literal_count + distance_count];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_length_code.decode(bits)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *i
+                    .checked_sub(1)
+                    .and_then(|p| lengths.get(p))
+                    .ok_or(Error::Corrupt("repeated length without a previous one"))?;
+                (previous, 3 + bits.take(2)? as usize)
+            }
+            17 => (0, 3 + bits.take(3)? as usize),
+            _ => (0, 11 + bits.take(7)? as usize),
+        };
+        let run = lengths
+            .get_mut(i..i + repeat)
+            .ok_or(Error::Corrupt("too many code lengths"))?;
+        run.fill(value);
+        i += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(Error::Corrupt("missing end of block code"));
+    }
+
+    let (literals, distances) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals)?, Huffman::new(distances)?))
+}
+
+fn compressed_block(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<()> {
+    loop {
+        let symbol = usize::from(literals.decode(bits)?);
+        if symbol == 256 {
+            return Ok(());
+        }
+        if out.len() >= limit {
+            return Err(Error::TooLarge);
+        }
+        if symbol < 256 {
+            out.push(symbol as u8);
+            continue;
+        }
+
+        let symbol = symbol - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(Error::Corrupt("invalid length code"));
+        }
+        let len =
+            usize::from(LENGTH_BASE[symbol]) + bits.take(u32::from(LENGTH_EXTRA[symbol]))? as usize;
+        let symbol = usize::from(distances.decode(bits)?);
+        if symbol >= DISTANCE_BASE.len() {
+            return Err(Error::Corrupt("invalid distance code"));
+        }
+        let distance = usize::from(DISTANCE_BASE[symbol])
+            + bits.take(u32::from(DISTANCE_EXTRA[symbol]))? as usize;
+        if distance > out.len() {
+            return Err(Error::Corrupt("distance too far back"));
+        }
+        if out.len() + len > limit {
+            return Err(Error::TooLarge);
+        }
+        let start = out.len() - distance;
+        for i in 0..len {
+            out.push(out[start + i]);
+        }
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
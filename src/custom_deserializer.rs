@@ -1,6 +1,25 @@
+use chrono::{DateTime, Utc};
+#[cfg(any(
+    feature = "email",
+    feature = "gamertag",
+    feature = "ln_address",
+    feature = "voucher",
+    feature = "withdrawal_request"
+))]
+use serde::de::Error;
 use serde::{Deserialize, Deserializer};
+#[cfg(any(
+    feature = "charges",
+    feature = "gamertag",
+    feature = "internal_transfer",
+    feature = "keysend",
+    feature = "payments"
+))]
+use serde_json::Value;
+#[cfg(any(feature = "email", feature = "voucher"))]
 use std::{fmt::Display, str::FromStr};
 
+#[cfg(any(feature = "email", feature = "voucher"))]
 pub fn deserialize_from_string<'de, T, D>(deserializer: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
@@ -11,6 +30,8 @@ where
         .parse::<T>()
         .map_err(serde::de::Error::custom)
 }
+
+#[cfg(feature = "voucher")]
 pub fn deserialize_from_m_string<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
 where
     D: Deserializer<'de>,
@@ -28,3 +49,71 @@ where
         Err(e) => Err(e),
     }
 }
+
+/// Parses a timestamp, naming the `field` and the offending value when it's malformed.
+/// Used through [`datetime_field`], as `deserialize_with` can't pass the field name.
+#[cfg(any(
+    feature = "email",
+    feature = "gamertag",
+    feature = "ln_address",
+    feature = "voucher",
+    feature = "withdrawal_request"
+))]
+pub fn deserialize_datetime<'de, D>(deserializer: D, field: &str) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse::<DateTime<Utc>>()
+        .map_err(|e| Error::custom(format!("invalid timestamp {value:?} for `{field}`: {e}")))
+}
+
+/// Defines a `deserialize_with` function for the required timestamp `$field`, so a malformed
+/// value fails with an error naming it.
+#[cfg(any(
+    feature = "email",
+    feature = "gamertag",
+    feature = "ln_address",
+    feature = "voucher",
+    feature = "withdrawal_request"
+))]
+macro_rules! datetime_field {
+    ($name:ident, $field:literal) => {
+        fn $name<'de, D>(deserializer: D) -> Result<chrono::DateTime<chrono::Utc>, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+        {
+            crate::custom_deserializer::deserialize_datetime(deserializer, $field)
+        }
+    };
+}
+#[cfg(any(
+    feature = "email",
+    feature = "gamertag",
+    feature = "ln_address",
+    feature = "voucher",
+    feature = "withdrawal_request"
+))]
+pub(crate) use datetime_field;
+
+/// Parses an optional timestamp, treating a malformed one as missing instead of
+/// failing the whole response.
+#[cfg(any(
+    feature = "charges",
+    feature = "gamertag",
+    feature = "internal_transfer",
+    feature = "keysend",
+    feature = "payments"
+))]
+pub fn deserialize_lenient_datetime<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(value)) => value.parse::<DateTime<Utc>>().ok(),
+        _ => None,
+    })
+}
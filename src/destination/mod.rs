@@ -0,0 +1,83 @@
+use crate::errors::ErrorMsg;
+use std::{fmt, str::FromStr};
+
+/// Where a pasted payment string points to, classified by its format.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Destination {
+    /// BOLT11 payment request, pay with `pay_invoice`
+    Bolt11(String),
+    /// Bech32 encoded LNURL
+    Lnurl(String),
+    /// `name@domain` Lightning Address, pay with `pay_ln_address`
+    LightningAddress(String),
+}
+
+impl TryFrom<&str> for Destination {
+    type Error = ErrorMsg;
+
+    /// Strips whitespace and any `lightning:` prefix, then classifies by prefix and shape only.
+    /// Nothing is decoded or looked up, so a match is not a guarantee the destination is valid.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let trimmed = value.trim();
+        let input = match trimmed.get(..10) {
+            Some(scheme) if scheme.eq_ignore_ascii_case("lightning:") => &trimmed[10..],
+            _ => trimmed,
+        };
+        let lower = input.to_lowercase();
+
+        if lower.starts_with("lnurl") {
+            Ok(Destination::Lnurl(lower))
+        } else if lower.starts_with("ln") && lower.contains('1') && !lower.contains('@') {
+            Ok(Destination::Bolt11(lower))
+        } else if is_lightning_address(input) {
+            Ok(Destination::LightningAddress(input.to_string()))
+        } else {
+            Err(ErrorMsg::UnknownDestination(value.to_string()))
+        }
+    }
+}
+
+fn is_lightning_address(input: &str) -> bool {
+    input.parse::<LightningAddress>().is_ok()
+}
+
+/// A `name@domain` Lightning Address split into its parts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LightningAddress {
+    pub name: String,
+    pub domain: String,
+}
+
+impl FromStr for LightningAddress {
+    type Err = ErrorMsg;
+
+    /// Checks the shape only, the domain isn't looked up.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bad = |reason: &str| ErrorMsg::BadLnAddress(s.to_string(), reason.to_string());
+
+        let (name, domain) = s.split_once('@').ok_or_else(|| bad("missing @"))?;
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return Err(bad("invalid name"));
+        }
+        if !domain.contains('.')
+            || domain.starts_with('.')
+            || domain.ends_with('.')
+            || domain.contains(|c: char| c == '@' || c.is_whitespace())
+        {
+            return Err(bad("invalid domain"));
+        }
+        Ok(LightningAddress {
+            name: name.to_string(),
+            domain: domain.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for LightningAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.domain)
+    }
+}
+
+#[cfg(test)]
+mod tests;
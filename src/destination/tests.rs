@@ -0,0 +1,25 @@
+use super::*;
+
+#[test]
+fn test_classify_destination() {
+    let invoice = "lnbc120n1p0tdjwmpp5ycws0d788cjeqp9rn2wwxfymrekj9n80wy2yrk66tuu3ga5wukfs";
+    assert_eq!(
+        Destination::try_from(invoice).unwrap(),
+        Destination::Bolt11(invoice.to_string())
+    );
+    let uppercase = format!("LIGHTNING:{}", invoice.to_uppercase());
+    assert_eq!(
+        Destination::try_from(uppercase.as_str()).unwrap(),
+        Destination::Bolt11(invoice.to_string())
+    );
+    assert!(matches!(
+        Destination::try_from("LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS"),
+        Ok(Destination::Lnurl(_))
+    ));
+    assert_eq!(
+        Destination::try_from(" satoshi@zbd.gg ").unwrap(),
+        Destination::LightningAddress(String::from("satoshi@zbd.gg"))
+    );
+    assert!(Destination::try_from("satoshi@localhost").is_err());
+    assert!(Destination::try_from("hello world").is_err());
+}
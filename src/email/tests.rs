@@ -23,7 +23,7 @@ async fn test_pay_email() {
     let r = zebedee_client.pay_email(&email_payment_req).await.unwrap();
     assert!(r.success);
 
-    match r.data {
+    match r.data.unwrap() {
         EmailPaymentRes::ExistingZbdAccount(data) => {
             assert!(r.message.map(|a| a.contains("Payment")).unwrap_or_default());
             println!("Account exists");
@@ -36,3 +36,21 @@ async fn test_pay_email() {
         }
     }
 }
+
+#[tokio::test]
+async fn test_email_payment_wire_format() {
+    let payment = EmailPaymentReqest {
+        email: String::from("andre@zebedee.io"),
+        amount: String::from("5000"),
+        comment: String::from("tip"),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&payment).unwrap(),
+        serde_json::json!({
+            "email": "andre@zebedee.io",
+            "amount": "5000",
+            "comment": "tip",
+        })
+    );
+}
@@ -1,8 +1,15 @@
-use crate::{custom_deserializer::deserialize_from_string, StdResp, VoucherData};
+use crate::{
+    custom_deserializer::{datetime_field, deserialize_from_string},
+    voucher::VoucherData,
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
-pub type EmailPaymentResponse = StdResp<EmailPaymentRes>;
+datetime_field!(deserialize_settled_at, "settledAt");
+
+pub type EmailPaymentResponse = StdResp<Option<EmailPaymentRes>>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -14,6 +21,8 @@ pub enum EmailPaymentRes {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct EmailPaymentData {
     pub id: String,
     // TODO: Convert from string to a proper Enum
@@ -21,23 +30,21 @@ pub struct EmailPaymentData {
     #[serde(deserialize_with = "deserialize_from_string")]
     pub amount: u64,
     pub comment: String,
-    #[serde(rename = "receiverId")]
     pub receiver_id: String,
-    #[serde(rename = "senderTxId")]
     pub sender_tx_id: String,
-    #[serde(rename = "settledAt")]
+    #[serde(deserialize_with = "deserialize_settled_at")]
     pub settled_at: DateTime<Utc>,
-    #[serde(rename = "transactionId")]
     pub transaction_id: String,
 }
 
 /// Send instant Bitcoin payments to any email
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct EmailPaymentReqest {
     /// Recipient email to send payment to.
     pub email: String,
     /// Total amount of satoshis to send (in millisatoshis).
     pub amount: String,
     /// comment to be sent with the payment (max 150 characters).
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub comment: String,
 }
@@ -0,0 +1,54 @@
+//! Paths of the ZEBEDEE REST API endpoints, relative to the client's domain. Ids are appended
+//! as further path segments, e.g. `CHARGES` followed by the charge id.
+
+// the one place the version is written, `API_VERSION` and the paths below all follow it
+macro_rules! api_version {
+    () => {
+        "v0"
+    };
+}
+
+/// Version of the REST API most endpoints are on.
+pub const API_VERSION: &str = api_version!();
+
+macro_rules! api {
+    ($path:literal) => {
+        concat!("/", api_version!(), "/", $path)
+    };
+}
+
+macro_rules! v1 {
+    ($path:literal) => {
+        concat!("/v1/", $path)
+    };
+}
+
+pub const WALLET: &str = api!("wallet");
+pub const KEYSEND_PAYMENT: &str = api!("keysend-payment");
+pub const CHARGES: &str = api!("charges");
+pub const PAYMENTS: &str = api!("payments");
+pub const WITHDRAWAL_REQUESTS: &str = api!("withdrawal-requests");
+pub const INTERNAL_TRANSFER: &str = api!("internal-transfer");
+pub const EMAIL_SEND_PAYMENT: &str = api!("email/send-payment");
+
+pub const GAMERTAG_SEND_PAYMENT: &str = api!("gamertag/send-payment");
+pub const GAMERTAG_CHARGES: &str = api!("gamertag/charges");
+pub const GAMERTAG_TRANSACTION: &str = api!("gamertag/transaction");
+/// Looks up the user id of a gamertag
+pub const USER_ID_GAMERTAG: &str = api!("user-id/gamertag");
+/// Looks up the gamertag of a user id
+pub const GAMERTAG_USER_ID: &str = api!("gamertag/user-id");
+
+pub const LN_ADDRESS_SEND_PAYMENT: &str = api!("ln-address/send-payment");
+pub const LN_ADDRESS_FETCH_CHARGE: &str = api!("ln-address/fetch-charge");
+pub const LN_ADDRESS_VALIDATE: &str = api!("ln-address/validate");
+
+pub const IS_SUPPORTED_REGION: &str = api!("is-supported-region");
+pub const PROD_IPS: &str = api!("prod-ips");
+pub const BTCUSD: &str = api!("btcusd");
+
+/// The OAuth endpoints are on `v1`
+pub const OAUTH2_AUTHORIZE: &str = v1!("oauth2/authorize");
+pub const OAUTH2_TOKEN: &str = v1!("oauth2/token");
+pub const OAUTH2_USER: &str = v1!("oauth2/user");
+pub const OAUTH2_WALLET: &str = v1!("oauth2/wallet");
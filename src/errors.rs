@@ -4,18 +4,54 @@ use std::fmt::Display;
 /// Zebedee Error
 #[derive(thiserror::Error, Debug)]
 pub enum ZebedeeError {
-    /// Error from reqwest crate which is used to make HTTP requests
+    /// The request timed out before a response was received
+    #[error("Request timed out: {0}")]
+    Timeout(reqwest::Error),
+    /// Unable to connect to the Zebedee REST API (DNS, TCP or TLS failure)
+    #[error("Unable to connect: {0}")]
+    Connect(reqwest::Error),
+    /// The response body could not be read
+    #[error("Unable to read response body: {0}")]
+    Body(reqwest::Error),
+    /// Any other error from reqwest crate which is used to make HTTP requests
     #[error("{0}")]
-    InvalidRequest(#[from] reqwest::Error),
+    Request(reqwest::Error),
     /// Serde json Errors when parsing
     #[error("Unable to parse json: {0}")]
     InvalidJson(#[from] serde_json::Error),
+    /// The response is valid JSON but doesn't match the expected type, with the path of the
+    /// offending field, e.g. `data.invoice.request`, and the raw response body
+    #[error("Unable to parse json at `{path}`: {source}")]
+    Deserialize {
+        path: String,
+        source: serde_json::Error,
+        body: String,
+    },
     /// Serde json Errors when parsing
     #[error("{0}")]
     Validate(#[from] validator::ValidationErrors),
     /// Error messages from Zebedee REST API
     #[error("{0}")]
     Api(ApiError),
+    /// 401, the API key is missing or wrong
+    #[error("Unauthorized calling {endpoint}, check the API key: {error}")]
+    Unauthorized { endpoint: String, error: ApiError },
+    /// 403, the API key is valid but the project lacks permission for this endpoint
+    #[error("Forbidden calling {endpoint}, the project lacks permission: {error}")]
+    Forbidden { endpoint: String, error: ApiError },
+    /// 404, with the last segment of the requested path, which is the id for lookups
+    #[error("Not found {id}: {error}")]
+    NotFound { id: String, error: ApiError },
+    /// 429, too many requests, see [`ZebedeeClient::rate_limit`](crate::ZebedeeClient::rate_limit)
+    /// for when the limit resets
+    #[error("Rate limited calling {endpoint}: {error}")]
+    RateLimited { endpoint: String, error: ApiError },
+    /// The configured API key was rejected
+    #[error("Invalid API key: {0}")]
+    InvalidApiKey(ApiError),
+    /// Standard OAuth error from the token endpoint, e.g. `invalid_grant` for an expired code
+    #[error("OAuth error: {0}")]
+    Oauth(OauthError),
     /// Internal Error messages
     #[error("{0}")]
     Msg(ErrorMsg),
@@ -29,6 +65,66 @@ pub struct ApiError {
     pub message: String,
     /// Status of API call
     pub success: bool,
+    /// Field level errors when the request body was rejected
+    #[serde(deserialize_with = "deserialize_validation_errors")]
+    pub errors: Vec<ValidationError>,
+    /// HTTP status of the response, `None` for errors reported in a successful response
+    #[serde(skip)]
+    pub status: Option<u16>,
+    /// Raw response body, for debugging errors that didn't come with a JSON message
+    #[serde(skip)]
+    pub body: Option<String>,
+}
+
+/// A request body field rejected by the Zebedee REST API
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ValidationError {
+    /// Name of the offending field
+    pub field: String,
+    /// Why the field was rejected
+    pub message: String,
+}
+
+/// Reads `errors` as field level errors, or as none when it comes in another shape, so the
+/// rest of the error body is still reported.
+fn deserialize_validation_errors<'de, D>(deserializer: D) -> Result<Vec<ValidationError>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+/// OAuth 2.0 error body (RFC 6749, section 5.2)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OauthError {
+    /// Error code such as `invalid_grant` or `invalid_client`
+    pub error: String,
+    /// Human readable explanation, when the server sends one
+    #[serde(rename = "error_description")]
+    pub description: Option<String>,
+}
+
+impl OauthError {
+    /// Reads an OAuth error body. Bodies that also carry a `message` are API or gateway
+    /// errors which merely have an `error` field, and are left to [`ApiError`].
+    pub(crate) fn from_body(body: &[u8]) -> Option<Self> {
+        let value = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+        if value.get("message").is_some() || !value.get("error")?.is_string() {
+            return None;
+        }
+        serde_json::from_value(value).ok()
+    }
+}
+
+impl Display for OauthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.description {
+            Some(description) => write!(f, "{}: {}", self.error, description),
+            None => f.write_str(&self.error),
+        }
+    }
 }
 
 /// General Error messages
@@ -43,6 +139,75 @@ pub enum ErrorMsg {
     /// Bad LN Address
     #[error("Bad LN Address {0}, ValidationError {1}")]
     BadLnAddress(String, String),
+    /// Configured domain is not a usable base url
+    #[error("Bad url {0}: {1}")]
+    BadUrl(String, String),
+    /// Invoice could not be decoded
+    #[error("Bad invoice: {0}")]
+    BadInvoice(String),
+    /// Payment amount doesn't fit the invoice
+    #[error("Bad payment amount: {0}")]
+    BadPaymentAmount(String),
+    /// The API returned a different internal id than the one sent
+    #[error("Internal id mismatch: sent {0:?}, received {1:?}")]
+    InternalIdMismatch(String, String),
+    /// Dry run mode can't synthesize a response for this call
+    #[error("Dry run, request to {0} not sent")]
+    DryRun(String),
+    /// Response body is larger than the configured maximum, in bytes
+    #[error("Response body exceeds {0} bytes")]
+    BodyTooLarge(usize),
+    /// Callback request didn't carry the expected secret
+    #[error("Callback secret missing or wrong")]
+    BadCallbackSecret,
+    /// Input isn't a recognizable invoice, LNURL or Lightning Address
+    #[error("Unknown payment destination {0}")]
+    UnknownDestination(String),
+    /// Response body isn't valid UTF-8, with its content type
+    #[error("Response body with content type {0} is not valid UTF-8: {1}")]
+    BadEncoding(String, String),
+    /// Response body couldn't be decoded per its content encoding
+    #[error("Unable to decode {0} encoded response body: {1}")]
+    BadContentEncoding(String, String),
+    /// API call succeeded but returned no data
+    #[error("No data returned for {0}")]
+    MissingData(String),
+    /// Gave up polling before reaching a final state
+    #[error("Timed out waiting for {0}")]
+    PollTimeout(String),
+    /// Polling was cancelled through its cancellation token
+    #[error("Cancelled waiting for {0}")]
+    Cancelled(String),
+}
+
+impl ZebedeeError {
+    /// Whether sending the same request again may succeed: true for timeouts, connection
+    /// failures, 5xx responses and 429 Too Many Requests, false for other client errors,
+    /// validation failures and everything else. For use in your own retry loops.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ZebedeeError::Timeout(_) | ZebedeeError::Connect(_) => true,
+            ZebedeeError::RateLimited { .. } => true,
+            ZebedeeError::Api(error) => error
+                .status
+                .is_some_and(|status| status == 429 || status >= 500),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ZebedeeError {
+    fn from(value: reqwest::Error) -> Self {
+        if value.is_timeout() {
+            ZebedeeError::Timeout(value)
+        } else if value.is_connect() {
+            ZebedeeError::Connect(value)
+        } else if value.is_body() || value.is_decode() {
+            ZebedeeError::Body(value)
+        } else {
+            ZebedeeError::Request(value)
+        }
+    }
 }
 
 impl From<ErrorMsg> for ZebedeeError {
@@ -59,6 +224,14 @@ impl From<ApiError> for ZebedeeError {
 
 impl Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.message.as_str())
+        f.write_str(self.message.as_str())?;
+        for (i, err) in self.errors.iter().enumerate() {
+            let sep = if i == 0 { " (" } else { ", " };
+            write!(f, "{sep}{}: {}", err.field, err.message)?;
+        }
+        if !self.errors.is_empty() {
+            f.write_str(")")?;
+        }
+        Ok(())
     }
 }
@@ -1,5 +1,8 @@
 use super::*;
-use crate::ZebedeeClient;
+use crate::{
+    test_utils::{MockResponse, MockServer},
+    ZebedeeClient,
+};
 use std::env;
 
 #[tokio::test]
@@ -91,3 +94,59 @@ async fn test_get_gamertag_by_userid() {
         .success;
     assert!(r);
 }
+
+#[tokio::test]
+async fn test_get_gamertag_static_charge() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"valid":true,"metadata":{"minSendable":1000,"maxSendable":500000000}}}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client
+        .get_gamertag_static_charge("satoshi")
+        .await
+        .unwrap();
+    assert!(r.success);
+
+    let request = server.requests.lock().unwrap()[0].clone();
+    assert!(request.starts_with("GET /v0/ln-address/validate/satoshi@zbd.gg "));
+}
+
+#[tokio::test]
+async fn test_pay_gamertag_null_data() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":null,"message":"Payment queued."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let payment = GamertagPayment {
+        gamertag: String::from("satoshi"),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let r = zebedee_client.pay_gamertag(&payment).await.unwrap();
+    assert!(r.success);
+    assert!(r.data.is_none());
+}
+
+#[tokio::test]
+async fn test_gamertag_payment_wire_format() {
+    let payment = GamertagPayment {
+        gamertag: String::from("santos"),
+        amount: String::from("5000"),
+        description: String::from("gg"),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&payment).unwrap(),
+        serde_json::json!({
+            "gamertag": "santos",
+            "amount": "5000",
+            "description": "gg",
+        })
+    );
+}
@@ -1,23 +1,42 @@
-use crate::StdResp;
+use crate::{
+    custom_deserializer::{datetime_field, deserialize_lenient_datetime},
+    ln_address::LnAddress,
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-pub type GamertagPayResponse = StdResp<GamertagPaymentData>;
+datetime_field!(deserialize_settled_at, "settledAt");
+datetime_field!(deserialize_invoice_expires_at, "invoiceExpiresAt");
+datetime_field!(deserialize_created_at, "createdAt");
+
+pub type GamertagPayResponse = StdResp<Option<GamertagPaymentData>>;
 pub type GamertagChargeResponse = StdResp<Option<GamertagChargeData>>;
 pub type GamertagTxResponse = StdResp<Option<GamertagTxData>>;
 pub type GamertagUserIdResponse = StdResp<Option<GamertagFromUserIdData>>;
 pub type IdFromGamertagResponse = StdResp<Option<IdFromUserGamertagData>>;
 
+/// Domain serving the static Lightning Address of every ZBD user
+pub const ZBD_LN_ADDRESS_DOMAIN: &str = "zbd.gg";
+
+/// Static Lightning Address (LNURL-pay) of a gamertag, e.g. `satoshi@zbd.gg`.
+/// Pay it with `pay_ln_address` to send to any ZBD user by gamertag.
+pub fn gamertag_ln_address(gamertag: &str) -> LnAddress {
+    LnAddress {
+        address: format!("{gamertag}@{ZBD_LN_ADDRESS_DOMAIN}"),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct GamertagPaymentData {
-    #[serde(rename = "receiverId")]
     pub receiver_id: String,
-    #[serde(rename = "transactionId")]
     pub transaction_id: String,
     pub amount: String,
     pub comment: String,
-    #[serde(rename = "settledAt")]
+    #[serde(deserialize_with = "deserialize_settled_at")]
     pub settled_at: DateTime<Utc>,
     pub status: String,
     pub id: String,
@@ -30,6 +49,7 @@ pub struct GamertagPayment {
     pub gamertag: String,
     #[validate(length(min = 4))]
     pub amount: String,
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub description: String,
 }
 impl Default for GamertagPayment {
@@ -43,43 +63,46 @@ impl Default for GamertagPayment {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct GamertagChargeData {
-    #[serde(rename = "invoiceRequest")]
     pub invoice_request: String,
-    #[serde(rename = "invoiceExpiresAt")]
+    #[serde(deserialize_with = "deserialize_invoice_expires_at")]
     pub invoice_expires_at: DateTime<Utc>,
     pub unit: String,
-    #[serde(rename = "createdAt")]
+    #[serde(deserialize_with = "deserialize_created_at")]
     pub created_at: DateTime<Utc>,
     pub status: String,
-    #[serde(rename = "internalId")]
     pub internal_id: Option<String>,
     pub amount: String,
     pub description: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct GamertagTxData {
     pub id: String,
-    #[serde(rename = "receiverId")]
     pub receiver_id: String,
     pub amount: String,
     pub fee: String,
     pub unit: String,
-    #[serde(rename = "processedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub processed_at: Option<DateTime<Utc>>,
-    #[serde(rename = "confirmedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub confirmed_at: Option<DateTime<Utc>>,
     pub comment: String,
     pub status: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct IdFromUserGamertagData {
     pub id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct GamertagFromUserIdData {
     pub gamertag: String,
 }
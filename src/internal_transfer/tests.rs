@@ -24,3 +24,19 @@ async fn test_internal_transfer() {
     };
     assert!(i.contains("Error processing transfer."));
 }
+
+#[tokio::test]
+async fn test_internal_transfer_wire_format() {
+    let transfer = InternalTransfer {
+        amount: String::from("5000"),
+        receiver_wallet_id: String::from("b904ee02-ec0b-4fd4-b99f-1f2d3d0001a6"),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&transfer).unwrap(),
+        serde_json::json!({
+            "amount": "5000",
+            "receiverWalletId": "b904ee02-ec0b-4fd4-b99f-1f2d3d0001a6",
+        })
+    );
+}
@@ -1,33 +1,30 @@
-use crate::StdResp;
+use crate::{custom_deserializer::deserialize_lenient_datetime, StdResp};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-pub type InternalTransferResponse = StdResp<InternalTransferData>;
+pub type InternalTransferResponse = StdResp<Option<InternalTransferData>>;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct InternalTransferData {
     pub id: String,
     pub status: String,
     pub amount: String,
-    #[serde(rename = "senderWalletId")]
     pub sender_wallet_id: String,
-    #[serde(rename = "receiverWalletId")]
     pub receiver_wallet_id: String,
-    #[serde(rename = "userId")]
     pub user_id: String,
-    #[serde(rename = "sendTxId")]
     pub send_tx_id: String,
-    #[serde(rename = "receiveTxId")]
     pub receive_tx_id: String,
-    #[serde(rename = "createdAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub created_at: Option<DateTime<Utc>>,
-    #[serde(rename = "updatedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub updated_at: Option<DateTime<Utc>>,
 }
 /// Use this struct to create a well crafted json body for your internal transfers
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct InternalTransfer {
     pub amount: String,
-    #[serde(rename = "receiverWalletId")]
     pub receiver_wallet_id: String,
 }
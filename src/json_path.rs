@@ -0,0 +1,422 @@
+//! Finds the JSON path of the field a response body failed to deserialize at, e.g.
+//! `data.invoice.request`, by running the deserializer through a wrapper that tracks where it
+//! is in the document.
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::{cell::RefCell, fmt};
+
+/// Deserializes `body` as `T` again and returns the path at which that fails, `.` for the
+/// document itself. Only called once a parse has already failed, so successful responses
+/// don't pay for the tracking.
+pub(crate) fn error_path<T: DeserializeOwned>(body: &str) -> String {
+    let track = Track::default();
+    let mut de = serde_json::Deserializer::from_str(body);
+    let _ = T::deserialize(Tracked {
+        de: &mut de,
+        chain: &Chain::Root,
+        track: &track,
+        key: None,
+    });
+    track.path()
+}
+
+/// Where the deserializer is, as a linked list from the current value up to the root.
+enum Chain<'a> {
+    Root,
+    Seq { parent: &'a Chain<'a>, index: usize },
+    Map { parent: &'a Chain<'a>, key: String },
+}
+
+impl fmt::Display for Chain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chain::Root => Ok(()),
+            Chain::Seq { parent, index } => write!(f, "{parent}[{index}]"),
+            Chain::Map {
+                parent: Chain::Root,
+                key,
+            } => f.write_str(key),
+            Chain::Map { parent, key } => write!(f, "{parent}.{key}"),
+        }
+    }
+}
+
+/// Holds the path of the innermost value that failed, which is the first one to report.
+#[derive(Default)]
+struct Track(RefCell<Option<String>>);
+
+impl Track {
+    fn trigger(&self, chain: &Chain) {
+        let mut path = self.0.borrow_mut();
+        if path.is_none() {
+            *path = Some(chain.to_string());
+        }
+    }
+
+    fn path(self) -> String {
+        match self.0.into_inner() {
+            Some(path) if !path.is_empty() => path,
+            _ => String::from("."),
+        }
+    }
+}
+
+/// Slot for the map key being read, so the following value knows its name.
+type KeySlot = RefCell<Option<String>>;
+
+struct Tracked<'a, 'b, D> {
+    de: D,
+    chain: &'a Chain<'a>,
+    track: &'b Track,
+    key: Option<&'b KeySlot>,
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {$(
+        fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let Tracked { de, chain, track, key } = self;
+            de.$method($($arg,)* Wrap { visitor, chain, track, key })
+                .inspect_err(|_| track.trigger(chain))
+        }
+    )*};
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Tracked<'_, '_, D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+}
+
+/// Passes the visited value on to `visitor`, wrapping nested deserializers and accessors so
+/// the path follows into them.
+struct Wrap<'a, 'b, X> {
+    visitor: X,
+    chain: &'a Chain<'a>,
+    track: &'b Track,
+    key: Option<&'b KeySlot>,
+}
+
+impl<X> Wrap<'_, '_, X> {
+    fn capture(&self, key: &str) {
+        if let Some(slot) = self.key {
+            *slot.borrow_mut() = Some(key.to_owned());
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {$(
+        fn $method<E: de::Error>(self, v: $ty) -> Result<X::Value, E> {
+            self.visitor.$method(v)
+        }
+    )*};
+}
+
+impl<'de, X: Visitor<'de>> Visitor<'de> for Wrap<'_, '_, X> {
+    type Value = X::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<X::Value, E> {
+        self.capture(v);
+        self.visitor.visit_str(v)
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<X::Value, E> {
+        self.capture(v);
+        self.visitor.visit_borrowed_str(v)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<X::Value, E> {
+        self.capture(&v);
+        self.visitor.visit_string(v)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<X::Value, E> {
+        self.visitor.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<X::Value, E> {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, de: D) -> Result<X::Value, D::Error> {
+        let (chain, track) = (self.chain, self.track);
+        self.visitor.visit_some(Tracked {
+            de,
+            chain,
+            track,
+            key: None,
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<X::Value, D::Error> {
+        let (chain, track) = (self.chain, self.track);
+        self.visitor.visit_newtype_struct(Tracked {
+            de,
+            chain,
+            track,
+            key: None,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<X::Value, A::Error> {
+        let (chain, track) = (self.chain, self.track);
+        self.visitor.visit_seq(TrackedSeq {
+            seq,
+            chain,
+            track,
+            index: 0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<X::Value, A::Error> {
+        let (chain, track) = (self.chain, self.track);
+        self.visitor.visit_map(TrackedMap {
+            map,
+            chain,
+            track,
+            key: None,
+        })
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<X::Value, A::Error> {
+        let (chain, track) = (self.chain, self.track);
+        self.visitor.visit_enum(TrackedEnum { data, chain, track })
+    }
+}
+
+struct TrackedSeed<'a, 'b, S> {
+    seed: S,
+    chain: &'a Chain<'a>,
+    track: &'b Track,
+    key: Option<&'b KeySlot>,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for TrackedSeed<'_, '_, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, de: D) -> Result<S::Value, D::Error> {
+        self.seed.deserialize(Tracked {
+            de,
+            chain: self.chain,
+            track: self.track,
+            key: self.key,
+        })
+    }
+}
+
+struct TrackedSeq<'a, 'b, A> {
+    seq: A,
+    chain: &'a Chain<'a>,
+    track: &'b Track,
+    index: usize,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for TrackedSeq<'_, '_, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let chain = Chain::Seq {
+            parent: self.chain,
+            index: self.index,
+        };
+        self.index += 1;
+        self.seq.next_element_seed(TrackedSeed {
+            seed,
+            chain: &chain,
+            track: self.track,
+            key: None,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.seq.size_hint()
+    }
+}
+
+struct TrackedMap<'a, 'b, A> {
+    map: A,
+    chain: &'a Chain<'a>,
+    track: &'b Track,
+    key: Option<String>,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for TrackedMap<'_, '_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let slot = KeySlot::default();
+        let key = self.map.next_key_seed(TrackedSeed {
+            seed,
+            chain: self.chain,
+            track: self.track,
+            key: Some(&slot),
+        });
+        self.key = slot.into_inner();
+        key
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let chain = Chain::Map {
+            parent: self.chain,
+            key: self.key.take().unwrap_or_else(|| String::from("?")),
+        };
+        self.map.next_value_seed(TrackedSeed {
+            seed,
+            chain: &chain,
+            track: self.track,
+            key: None,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+struct TrackedEnum<'a, 'b, A> {
+    data: A,
+    chain: &'a Chain<'a>,
+    track: &'b Track,
+}
+
+impl<'a, 'b, 'de, A: EnumAccess<'de>> EnumAccess<'de> for TrackedEnum<'a, 'b, A> {
+    type Error = A::Error;
+    type Variant = TrackedEnum<'a, 'b, A::Variant>;
+
+    fn variant_seed<S>(self, seed: S) -> Result<(S::Value, Self::Variant), A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let (chain, track) = (self.chain, self.track);
+        let (value, data) = self.data.variant_seed(TrackedSeed {
+            seed,
+            chain,
+            track,
+            key: None,
+        })?;
+        Ok((value, TrackedEnum { data, chain, track }))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for TrackedEnum<'_, '_, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.data.unit_variant()
+    }
+
+    fn newtype_variant_seed<S>(self, seed: S) -> Result<S::Value, A::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        self.data.newtype_variant_seed(TrackedSeed {
+            seed,
+            chain: self.chain,
+            track: self.track,
+            key: None,
+        })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        self.data.tuple_variant(
+            len,
+            Wrap {
+                visitor,
+                chain: self.chain,
+                track: self.track,
+                key: None,
+            },
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        self.data.struct_variant(
+            fields,
+            Wrap {
+                visitor,
+                chain: self.chain,
+                track: self.track,
+                key: None,
+            },
+        )
+    }
+}
@@ -24,3 +24,25 @@ async fn test_keysend() {
         .success;
     assert!(r);
 }
+
+#[tokio::test]
+async fn test_keysend_wire_format() {
+    let keysend = Keysend {
+        amount: String::from("5000"),
+        pubkey: String::from("0332d57355d673e217238ce3e4be8491aa6b2a13f95494133ee243e57df1653ace"),
+        tlv_records: vec![TlvRecord { record_type: 123123123, value: String::from("00ABCDEF") }],
+        metadata: String::from("{}"),
+        callback_url: String::from("https://example.com/callback"),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&keysend).unwrap(),
+        serde_json::json!({
+            "amount": "5000",
+            "pubkey": "0332d57355d673e217238ce3e4be8491aa6b2a13f95494133ee243e57df1653ace",
+            "tlvRecords": [{ "type": 123123123, "value": "00ABCDEF" }],
+            "metadata": "{}",
+            "callbackUrl": "https://example.com/callback",
+        })
+    );
+}
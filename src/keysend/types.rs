@@ -1,30 +1,30 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use crate::StdResp;
+use crate::{custom_deserializer::deserialize_lenient_datetime, StdResp};
 
 pub type KeysendResponse = StdResp<Option<KeysendData>>;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct KeysendTx {
     pub id: String,
-    #[serde(rename = "walletId")]
     pub wallet_id: String,
     pub r#type: Option<String>,
-    #[serde(rename = "totalAmount")]
     pub total_amount: String,
     pub fee: String,
     pub amount: String,
     pub description: Option<String>,
     pub status: String,
-    #[serde(rename = "confirmedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub confirmed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct KeysendData {
-    #[serde(rename = "keysendId")]
     pub keysend_id: String,
-    #[serde(rename = "paymentId")]
     pub payment_id: String,
     pub transaction: KeysendTx,
 }
@@ -32,13 +32,12 @@ pub struct KeysendData {
 /// Use this struct to create a well crafted json body for your keysend payments
 
 #[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct Keysend {
     pub amount: String,
     pub pubkey: String,
-    #[serde(rename = "tlvRecords")]
     pub tlv_records: Vec<TlvRecord>,
     pub metadata: String,
-    #[serde(rename = "callbackUrl")]
     pub callback_url: String,
 }
 
@@ -1,70 +1,284 @@
+pub mod bolt11;
+#[cfg(feature = "charges")]
+pub mod callbacks;
+#[cfg(feature = "charges")]
 pub mod charges;
+pub mod command;
+mod compression;
+#[cfg(any(
+    feature = "charges",
+    feature = "email",
+    feature = "gamertag",
+    feature = "internal_transfer",
+    feature = "keysend",
+    feature = "ln_address",
+    feature = "payments",
+    feature = "voucher",
+    feature = "withdrawal_request"
+))]
 mod custom_deserializer;
+pub mod destination;
+#[cfg(feature = "email")]
 pub mod email;
+pub mod endpoints;
 pub mod errors;
+#[cfg(feature = "gamertag")]
 pub mod gamertag;
+#[cfg(feature = "internal_transfer")]
 pub mod internal_transfer;
+mod json_path;
+#[cfg(feature = "keysend")]
 pub mod keysend;
+#[cfg(feature = "ln_address")]
 pub mod ln_address;
+pub mod logging;
+#[cfg(feature = "oauth")]
 pub mod login_with_zbd;
-mod models;
+pub mod metrics;
+pub mod models;
+#[cfg(feature = "payments")]
 pub mod payments;
+#[cfg(any(feature = "charges", feature = "payments"))]
+pub mod polling;
+pub mod rate_limit;
+pub mod retry;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "utilities")]
 pub mod utilities;
+#[cfg(feature = "voucher")]
 pub mod voucher;
 pub mod wallet;
+#[cfg(feature = "withdrawal_request")]
 pub mod withdrawal_request;
 
+#[cfg(feature = "charges")]
 use charges::*;
+#[cfg(feature = "email")]
 use email::*;
 use errors::*;
+#[cfg(feature = "charges")]
+use futures::{future, stream, Stream, StreamExt};
+#[cfg(feature = "gamertag")]
 use gamertag::*;
+#[cfg(feature = "internal_transfer")]
 use internal_transfer::*;
+#[cfg(feature = "keysend")]
 use keysend::*;
+#[cfg(feature = "ln_address")]
 use ln_address::*;
+use logging::Redact;
+#[cfg(feature = "oauth")]
 use login_with_zbd::*;
+use metrics::Metrics;
+#[cfg(any(feature = "charges", feature = "withdrawal_request"))]
+use models::ListParams;
+#[cfg(feature = "payments")]
 use payments::*;
+#[cfg(feature = "oauth")]
 use rand::Rng;
-use reqwest::{RequestBuilder, Response};
+use rate_limit::RateLimit;
+use reqwest::{
+    header::{ACCEPT_ENCODING, ACCEPT_LANGUAGE, CONTENT_ENCODING, CONTENT_TYPE},
+    RequestBuilder, Response, StatusCode, Url,
+};
+use retry::RetryPolicy;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::error::Category;
+#[cfg(feature = "oauth")]
 use sha2::{Digest, Sha256};
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+#[cfg(feature = "utilities")]
 use utilities::*;
+#[cfg(any(
+    feature = "charges",
+    feature = "email",
+    feature = "gamertag",
+    feature = "ln_address",
+    feature = "oauth",
+    feature = "payments",
+    feature = "withdrawal_request"
+))]
 use validator::Validate;
-use voucher::*;
 use wallet::*;
+#[cfg(feature = "withdrawal_request")]
 use withdrawal_request::*;
 
 pub type Result<T, E = errors::ZebedeeError> = std::result::Result<T, E>;
 
+/// Client for the ZEBEDEE REST API.
+///
+/// Every request method is cancellation safe: dropping a returned future aborts the in-flight
+/// HTTP request and releases its connection, and no blocking work is done on the executor.
 #[derive(Clone, Debug)]
 pub struct ZebedeeClient {
     domain: String,
+    fallback_domain: Option<String>,
     reqw_cli: reqwest::Client,
+    /// `http_version` and `connect_timeout` that this crate built `reqw_cli` with, `None` for
+    /// a client supplied with `reqw_cli`, which is never replaced
+    reqw_cli_settings: Option<(HttpVersion, Option<Duration>)>,
     apikey: String,
+    apikey_header: String,
+    locale: Option<String>,
+    dry_run: bool,
+    max_body_size: Option<usize>,
+    retry_policy: RetryPolicy,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    http_version: HttpVersion,
+    #[cfg(feature = "oauth")]
     oauth: ZebedeeOauth,
+    metrics: Arc<Metrics>,
+    rate_limit: Arc<Mutex<Option<RateLimit>>>,
 }
 
 impl ZebedeeClient {
+    /// Creates a client with its own connection pool. Reuse (or clone) it rather than creating
+    /// one per call, otherwise every request pays for a fresh TCP and TLS handshake.
     pub fn new() -> Self {
         ZebedeeClient::default()
     }
 
+    /// The default configuration around `reqw_cli`, so no other `reqwest::Client` is created.
+    fn with_reqw_cli(
+        reqw_cli: reqwest::Client,
+        reqw_cli_settings: Option<(HttpVersion, Option<Duration>)>,
+    ) -> Self {
+        ZebedeeClient {
+            domain: String::from("https://api.zebedee.io"),
+            fallback_domain: None,
+            reqw_cli,
+            reqw_cli_settings,
+            apikey: String::from("errornotset"),
+            apikey_header: String::from("apikey"),
+            locale: None,
+            dry_run: false,
+            max_body_size: None,
+            retry_policy: Default::default(),
+            timeout: None,
+            connect_timeout: None,
+            http_version: HttpVersion::Auto,
+            #[cfg(feature = "oauth")]
+            oauth: Default::default(),
+            metrics: Default::default(),
+            rate_limit: Default::default(),
+        }
+    }
+
+    /// Creates a client backed by one process-wide connection pool, so short-lived clients
+    /// still reuse open connections. The pool is used as is, like a client set with `reqw_cli`.
+    pub fn shared(apikey: String) -> Self {
+        static REQW_CLI: OnceLock<reqwest::Client> = OnceLock::new();
+        ZebedeeClient {
+            apikey,
+            ..ZebedeeClient::with_reqw_cli(REQW_CLI.get_or_init(reqwest::Client::new).clone(), None)
+        }
+    }
+
+    /// Creates a client for the API at `base_url` instead of production, e.g. a staging
+    /// environment or a local mock server in tests. Same as setting `domain`.
+    pub fn with_base_url(apikey: String, base_url: String) -> Self {
+        ZebedeeClient::new().apikey(apikey).domain(base_url).build()
+    }
+
     /// Zebedee REST API url
     pub fn domain(mut self, domain: String) -> Self {
         self.domain = domain;
         self
     }
 
+    /// Secondary API url, tried once for a request when connecting to `domain` fails.
+    /// Since the request never reached the primary, this applies to payments too.
+    pub fn fallback_domain(mut self, fallback_domain: String) -> Self {
+        self.fallback_domain = Some(fallback_domain);
+        self
+    }
+
     /// Project API key
     pub fn apikey(mut self, apikey: String) -> Self {
         self.apikey = apikey;
         self
     }
 
+    /// Header carrying the API key, `apikey` by default.
+    /// Override it when an auth-injecting gateway in front of the API expects another name.
+    pub fn apikey_header(mut self, apikey_header: String) -> Self {
+        self.apikey_header = apikey_header;
+        self
+    }
+
+    /// Sent as `Accept-Language` on every request so API messages come back localized,
+    /// e.g. `es` or `pt-BR`.
+    pub fn locale(mut self, locale: String) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// When set, payments, charges, withdrawal requests and other mutating calls are only
+    /// logged and answered with a synthesized success response without `data`, so code paths
+    /// can be exercised against production keys. Read-only calls still hit the API.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Caps how many bytes of a response body are read, failing with
+    /// `ErrorMsg::BodyTooLarge` instead of buffering anything bigger. A `gzip` or `deflate`
+    /// encoded body is held to the same cap once decoded. Unlimited by default.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = Some(max_body_size);
+        self
+    }
+
+    /// Which requests are resent after a timeout, none by default.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Deadline for each request, overriding any timeout set on the `reqwest::Client`.
+    /// Covers the whole request including connecting, see `connect_timeout` to fail faster
+    /// when the API can't be reached.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Deadline for establishing a connection, separate from the `timeout` for the whole
+    /// request, e.g. a few seconds to fail fast when ZBD is down while payments still get
+    /// time to route. Ignored, with a warning, when the `reqwest::Client` was supplied with
+    /// `reqw_cli`; set it on that client instead.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Returns a copy of this client with a different request timeout, sharing the connection
+    /// pool and metrics. Cheap enough to keep one per subsystem, e.g. a tight deadline for
+    /// user-facing calls and a lenient one for background reconciliation.
+    pub fn clone_with_timeout(&self, timeout: Duration) -> Self {
+        self.clone().timeout(timeout).build()
+    }
+
+    /// HTTP protocol version to speak. Ignored, with a warning, when the `reqwest::Client` was
+    /// supplied with `reqw_cli`; set it on that client instead.
+    pub fn http_version(mut self, http_version: HttpVersion) -> Self {
+        self.http_version = http_version;
+        self
+    }
+
+    /// Uses `reqw_cli` for every request, as is. `http_version` and `connect_timeout` only
+    /// apply to clients this crate builds itself.
     pub fn reqw_cli(mut self, reqw_cli: reqwest::Client) -> Self {
         self.reqw_cli = reqw_cli;
+        self.reqw_cli_settings = None;
         self
     }
+    #[cfg(feature = "oauth")]
     pub fn oauth(
         mut self,
         client_id: String,
@@ -79,110 +293,577 @@ impl ZebedeeClient {
     }
 
     pub fn build(self) -> Self {
+        let settings = (self.http_version, self.connect_timeout);
+        let (reqw_cli, reqw_cli_settings) = match self.reqw_cli_settings {
+            Some(built) if built != settings => {
+                let mut builder = match self.http_version {
+                    HttpVersion::Auto => reqwest::Client::builder(),
+                    HttpVersion::Http1Only => reqwest::Client::builder().http1_only(),
+                    HttpVersion::Http2PriorKnowledge => {
+                        reqwest::Client::builder().http2_prior_knowledge()
+                    }
+                };
+                if let Some(connect_timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(connect_timeout);
+                }
+                // only fails when the TLS backend can't be initialized
+                match builder.build() {
+                    Ok(reqw_cli) => (reqw_cli, Some(settings)),
+                    Err(e) => {
+                        log::warn!("{e}, keeping the previous HTTP client settings");
+                        (self.reqw_cli, Some(built))
+                    }
+                }
+            }
+            Some(built) => (self.reqw_cli, Some(built)),
+            None => {
+                if settings != (HttpVersion::Auto, None) {
+                    log::warn!(
+                        "http_version and connect_timeout are ignored for a client set with reqw_cli"
+                    );
+                }
+                (self.reqw_cli, None)
+            }
+        };
         ZebedeeClient {
             domain: self.domain,
-            reqw_cli: self.reqw_cli,
+            fallback_domain: self.fallback_domain,
+            reqw_cli,
+            reqw_cli_settings,
             apikey: self.apikey,
+            apikey_header: self.apikey_header,
+            locale: self.locale,
+            dry_run: self.dry_run,
+            max_body_size: self.max_body_size,
+            retry_policy: self.retry_policy,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            http_version: self.http_version,
+            #[cfg(feature = "oauth")]
             oauth: self.oauth,
+            metrics: self.metrics,
+            rate_limit: self.rate_limit,
         }
     }
 
+    /// The configured API url, e.g. to log which environment a client targets.
+    pub fn base_url(&self) -> &str {
+        &self.domain
+    }
+
+    /// Version of the REST API this crate calls. The OAuth endpoints are on `v1`.
+    pub fn api_version(&self) -> &'static str {
+        endpoints::API_VERSION
+    }
+
+    /// Request counters, shared with every clone of this client.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Rate limit reported by the most recent response that carried the `X-RateLimit-*`
+    /// headers, shared with every clone of this client.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    async fn send(&self, mut request_builder: RequestBuilder) -> Result<Response> {
+        if let Some(locale) = &self.locale {
+            request_builder = request_builder.header(ACCEPT_LANGUAGE, locale);
+        }
+        request_builder = request_builder.header(ACCEPT_ENCODING, compression::ACCEPTED_ENCODINGS);
+        let mut request = request_builder.build()?;
+        if let Some(timeout) = self.timeout {
+            *request.timeout_mut() = Some(timeout);
+        }
+
+        let mut fallback = self.fallback_domain.as_deref().and_then(|fallback_domain| {
+            let mut fallback = request.try_clone()?;
+            *fallback.url_mut() = self.rebase_url(fallback.url(), fallback_domain)?;
+            Some(fallback)
+        });
+
+        let mut attempt = 0;
+        loop {
+            // streaming bodies can't be cloned and so are never retried
+            let retry = (attempt < self.retry_policy.max_retries())
+                .then(|| request.try_clone())
+                .flatten();
+
+            self.metrics.record_request();
+            match self.reqw_cli.execute(request).await {
+                Ok(resp) => {
+                    self.metrics.record_status(resp.status());
+                    if let Some(rate_limit) = RateLimit::from_headers(resp.headers()) {
+                        *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner()) =
+                            Some(rate_limit);
+                    }
+                    return Ok(resp);
+                }
+                Err(e) => {
+                    self.metrics.record_transport_error();
+                    if e.is_connect() {
+                        if let Some(next) = fallback.take() {
+                            log::warn!("{e}, falling back to {}", next.url());
+                            request = next;
+                            continue;
+                        }
+                    }
+                    match retry {
+                        Some(next)
+                            if e.is_timeout()
+                                && self.retry_policy.should_retry(
+                                    attempt,
+                                    next.method(),
+                                    next.url().path(),
+                                ) =>
+                        {
+                            self.metrics.record_retry();
+                            let delay = self.retry_policy.delay(attempt);
+                            if !delay.is_zero() {
+                                tokio::time::sleep(delay).await;
+                            }
+                            request = next;
+                            attempt += 1;
+                        }
+                        _ => return Err(e.into()),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends a request that moves funds or creates resources, honouring `dry_run`.
+    // unused when every mutating endpoint feature is disabled
+    #[allow(dead_code)]
+    async fn send_mutation<T>(&self, request_builder: RequestBuilder) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.dry_run {
+            let resp = self.send(request_builder).await?;
+            return self.parse_response(resp).await;
+        }
+
+        let request = request_builder.build()?;
+        // the body carries invoices, internal ids and callback secrets, log it redacted
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .and_then(|body| serde_json::from_slice::<serde_json::Value>(body).ok())
+            .and_then(|body| serde_json::to_string(&body.redacted()).ok())
+            .unwrap_or_default();
+        log::info!("dry run: {} {} {}", request.method(), request.url(), body);
+
+        let synthesized =
+            serde_json::json!({ "success": true, "data": null, "message": "dry run" });
+        serde_json::from_value(synthesized)
+            .map_err(|_| ErrorMsg::DryRun(request.url().to_string()).into())
+    }
+
     async fn parse_response<T>(&self, resp: Response) -> Result<T>
     where
         T: DeserializeOwned,
     {
-        let is_success = resp.status().is_success();
+        self.parse_response_with(resp, false).await
+    }
+
+    /// Like `parse_response`, for the OAuth token endpoint, whose errors are standard OAuth
+    /// error bodies rather than ZEBEDEE API errors.
+    #[cfg(feature = "oauth")]
+    async fn parse_token_response<T>(&self, resp: Response) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.parse_response_with(resp, true).await
+    }
+
+    async fn parse_response_with<T>(&self, resp: Response, oauth_errors: bool) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let status = resp.status();
+        let is_success = status.is_success();
+        let endpoint = resp.url().path().to_string();
+        let last_segment = resp
+            .url()
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default()
+            .to_string();
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("unknown")
+            .to_string();
         // parse the resp body
-        let body = resp.json::<Value>().await?;
+        let body = self.read_body(resp).await?;
 
-        // based on success or error choose the appropriate data structure to deserialize
-        match is_success {
-            true => {
-                let body = serde_json::from_value::<T>(body)?;
-                Ok(body)
+        if oauth_errors && !is_success {
+            if let Some(error) = OauthError::from_body(&body) {
+                return Err(ZebedeeError::Oauth(error));
             }
-            false => {
-                let err_body: ApiError = serde_json::from_value(body)?;
-                Err(err_body.into())
+        }
+
+        // auth failures may come from a gateway in front of the API, so the body is optional
+        if let StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN = status {
+            let error = ApiError {
+                status: Some(status.as_u16()),
+                body: Some(String::from_utf8_lossy(&body).into_owned()),
+                ..serde_json::from_slice::<ApiError>(&body).unwrap_or_default()
+            };
+            return Err(match status {
+                StatusCode::UNAUTHORIZED => ZebedeeError::Unauthorized { endpoint, error },
+                _ => ZebedeeError::Forbidden { endpoint, error },
+            });
+        }
+
+        let raw_body = std::str::from_utf8(&body)
+            .map_err(|e| ErrorMsg::BadEncoding(content_type, e.to_string()))?;
+
+        // errors without a JSON body, e.g. a gateway's 502 page, still report their status
+        if !is_success {
+            let error = serde_json::from_str::<ApiError>(raw_body).unwrap_or_else(|_| ApiError {
+                message: status.to_string(),
+                ..Default::default()
+            });
+            let error = ApiError {
+                status: Some(status.as_u16()),
+                body: Some(raw_body.to_string()),
+                ..error
+            };
+            return Err(match status {
+                StatusCode::NOT_FOUND => ZebedeeError::NotFound {
+                    id: last_segment,
+                    error,
+                },
+                StatusCode::TOO_MANY_REQUESTS => ZebedeeError::RateLimited { endpoint, error },
+                _ => error.into(),
+            });
+        }
+
+        // malformed JSON is reported as such rather than as a mismatch with T
+        let parsed = match serde_json::from_str::<T>(raw_body) {
+            Err(e) if e.classify() != Category::Data => return Err(e.into()),
+            parsed => parsed,
+        };
+        // logical failures the API reports with a 2xx status
+        if let Ok(Outcome {
+            success: Some(false),
+        }) = serde_json::from_str(raw_body)
+        {
+            let error = ApiError {
+                body: Some(raw_body.to_string()),
+                ..serde_json::from_str::<ApiError>(raw_body).unwrap_or_default()
+            };
+            return Err(error.into());
+        }
+        parsed.map_err(|source| ZebedeeError::Deserialize {
+            path: json_path::error_path::<T>(raw_body),
+            source,
+            body: raw_body.to_string(),
+        })
+    }
+
+    /// Reads the whole response body, decoding it if it was sent `gzip` or `deflate` encoded.
+    /// `max_body_size` applies to the body both as received and once decoded.
+    async fn read_body(&self, resp: Response) -> Result<Vec<u8>> {
+        let encoding = resp
+            .headers()
+            .get(CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_ascii_lowercase());
+        let body = self.read_raw_body(resp).await?;
+        match encoding.as_deref() {
+            None | Some("" | "identity") => Ok(body),
+            Some(encoding) => compression::decode(encoding, &body, self.max_body_size),
+        }
+    }
+
+    /// Reads the body as received, stopping early once it exceeds `max_body_size`.
+    async fn read_raw_body(&self, mut resp: Response) -> Result<Vec<u8>> {
+        let Some(limit) = self.max_body_size else {
+            return Ok(resp.bytes().await?.to_vec());
+        };
+        if resp.content_length().is_some_and(|len| len > limit as u64) {
+            return Err(ErrorMsg::BodyTooLarge(limit).into());
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = resp.chunk().await? {
+            if body.len() + chunk.len() > limit {
+                return Err(ErrorMsg::BodyTooLarge(limit).into());
             }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    /// Moves a url built by [`ZebedeeClient::url`] over to another base url, keeping its
+    /// already encoded path and query as they are.
+    fn rebase_url(&self, url: &Url, base: &str) -> Option<Url> {
+        let primary = Url::parse(&self.domain).ok()?;
+        let rest = url
+            .as_str()
+            .strip_prefix(primary.as_str().trim_end_matches('/'))?;
+        let base = Url::parse(base).ok()?;
+        Url::parse(&format!("{}{}", base.as_str().trim_end_matches('/'), rest)).ok()
+    }
+
+    /// Builds a request url from a fixed API `path` followed by `segments`, which are
+    /// percent-encoded so an id containing `/`, `?` or `#` can't change the endpoint called.
+    fn url(&self, path: &str, segments: &[&str]) -> Result<Url> {
+        let bad_url = |reason: String| ErrorMsg::BadUrl(self.domain.clone(), reason);
+        // dot segments would be resolved by the url parser and walk up the path
+        if let Some(segment) = segments.iter().find(|s| matches!(**s, "" | "." | "..")) {
+            return Err(ErrorMsg::BadPayloadData(format!("invalid id `{}`", segment)).into());
         }
+
+        let mut url = Url::parse(&self.domain).map_err(|e| bad_url(e.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|_| bad_url(String::from("cannot be a base url")))?
+            .pop_if_empty()
+            .extend(path.split('/').filter(|s| !s.is_empty()))
+            .extend(segments);
+        Ok(url)
     }
 
     fn add_headers(&self, request_builder: RequestBuilder) -> RequestBuilder {
         request_builder
             .header("Content-Type", "application/json")
-            .header("apikey", &self.apikey)
+            .header(self.apikey_header.as_str(), &self.apikey)
     }
 
     /// Retrieves the total balance of a given Project Wallet.
     pub async fn get_wallet_details(&self) -> Result<WalletInfoResponse> {
-        let url = format!("{}/v0/wallet", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::WALLET, &[])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
-    /// Make payment directly to a Lightning Network node Public Key, without the need for a Payment Request / Charge.
-    pub async fn keysend(&self, keysend_payload: &Keysend) -> Result<KeysendResponse> {
-        let url = format!("{}/v0/keysend-payment", &self.domain);
+    /// Checks that the configured API key is accepted by making a lightweight authenticated request.
+    /// Call this at startup to get a clear `InvalidApiKey` error instead of a failure on the first real call.
+    /// A key that is valid but lacks permission for the wallet endpoint fails with `Forbidden`.
+    pub async fn is_valid_key(&self) -> Result<()> {
+        let url = self.url(endpoints::WALLET, &[])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
+
+        match self.parse_response::<WalletInfoResponse>(resp).await {
+            Ok(_) => Ok(()),
+            Err(ZebedeeError::Unauthorized { error, .. }) => {
+                Err(ZebedeeError::InvalidApiKey(error))
+            }
+            Err(e) => Err(e),
+        }
+    }
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(keysend_payload)
-            .send()
-            .await?;
+    /// Opens a connection to the API host with an unauthenticated `HEAD` request so the first
+    /// real call doesn't pay for DNS and the TLS handshake. Any HTTP response counts as warmed
+    /// up; only transport errors are returned. Sent like any other request, so the timeout and
+    /// fallback domain apply and it is counted in [`ZebedeeClient::metrics`].
+    pub async fn warmup(&self) -> Result<()> {
+        let url = self.url("/", &[])?;
+        self.send(self.reqw_cli.head(url)).await?;
+        Ok(())
+    }
+
+    /// Starts a request to an endpoint this crate doesn't wrap yet, e.g.
+    /// `request(Method::GET, "/v0/new-endpoint")`, with the API key header already set.
+    /// Pass it to [`ZebedeeClient::send_request`] to get retries, metrics and error mapping.
+    pub fn request(&self, method: reqwest::Method, path: &str) -> Result<RequestBuilder> {
+        let url = self.url(path, &[])?;
+        Ok(self.add_headers(self.reqw_cli.request(method, url)))
+    }
 
+    /// Sends a request built with [`ZebedeeClient::request`] the way the wrapped endpoints are
+    /// sent, and parses the response body as `T`, e.g. `StdResp<serde_json::Value>`.
+    pub async fn send_request<T>(&self, request_builder: RequestBuilder) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let resp = self.send(request_builder).await?;
         self.parse_response(resp).await
     }
 
+    /// Make payment directly to a Lightning Network node Public Key, without the need for a Payment Request / Charge.
+    #[cfg(feature = "keysend")]
+    pub async fn keysend(&self, keysend_payload: &Keysend) -> Result<KeysendResponse> {
+        let url = self.url(endpoints::KEYSEND_PAYMENT, &[])?;
+
+        self.send_mutation(
+            self.add_headers(self.reqw_cli.post(url))
+                .json(keysend_payload),
+        )
+        .await
+    }
+
     /// Creates a new Charge / Payment Request in the Bitcoin Lightning Network, payable by any Lightning Network wallet.
     /// These payment requests are single-use, fixed-amount QR codes. If you're looking for multi-use and multi-amount
     /// payment requests you want Static Charges.
+    #[cfg(feature = "charges")]
     pub async fn create_charge(&self, charge: &Charge) -> Result<FetchOneChargeResponse> {
-        let url = format!("{}/v0/charges", &self.domain);
+        charge.validate()?;
+
+        let signed;
+        let charge = match &charge.callback_secret {
+            Some(secret) => {
+                signed = Charge {
+                    callback_url: callbacks::callback_url_with_secret(
+                        &charge.callback_url,
+                        secret,
+                    )?,
+                    ..charge.clone()
+                };
+                &signed
+            }
+            None => charge,
+        };
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(&charge)
-            .send()
-            .await?;
+        let url = self.url(endpoints::CHARGES, &[])?;
 
-        self.parse_response(resp).await
+        self.send_mutation(self.add_headers(self.reqw_cli.post(url)).json(&charge))
+            .await
+    }
+
+    /// Creates a Charge and returns just what a payment screen needs to show it.
+    /// Fails with `MissingData` in dry run mode, which creates no charge.
+    #[cfg(feature = "charges")]
+    pub async fn create_checkout(&self, charge: &Charge) -> Result<Checkout> {
+        let charge = self
+            .create_charge(charge)
+            .await?
+            .data
+            .ok_or_else(|| ErrorMsg::MissingData(String::from(endpoints::CHARGES)))?;
+        Ok(Checkout::try_from(charge)?)
     }
 
-    pub async fn get_charges(&self) -> Result<FetchChargesResponse> {
-        let url = format!("{}/v0/charges", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+    /// Retrieves all Charges, optionally narrowed down with `params`.
+    #[cfg(feature = "charges")]
+    pub async fn get_charges(&self, params: Option<&ListParams>) -> Result<FetchChargesResponse> {
+        let url = self.url(endpoints::CHARGES, &[])?;
+        let mut request_builder = self.add_headers(self.reqw_cli.get(url));
+        if let Some(params) = params {
+            request_builder = request_builder.query(params).query(&params.extra_params);
+        }
+
+        let resp = self.send(request_builder).await?;
         self.parse_response(resp).await
     }
 
+    /// Like [`ZebedeeClient::get_charges`] but returns the charges themselves. Responses with
+    /// `success: false` already fail with `ZebedeeError::Api`.
+    #[cfg(feature = "charges")]
+    pub async fn list_charges(&self, params: Option<&ListParams>) -> Result<Vec<ChargesData>> {
+        Ok(self.get_charges(params).await?.data.unwrap_or_default())
+    }
+
+    /// Pages through the Charges matching `params` with `?page` and `?limit`, `limit` charges
+    /// at a time, starting from its `page` or the first one, while fetching up to `prefetch`
+    /// pages concurrently ahead of the one being consumed.
+    /// The stream ends after an empty page, a page shorter than `limit`, a page with the same
+    /// charges as the one before it (the API ignoring `page`), the first error, or
+    /// [`MAX_CHARGE_PAGES`] pages. Pages past the end may be requested before that is known.
+    #[cfg(feature = "charges")]
+    pub fn get_charges_pages(
+        &self,
+        params: ListParams,
+        limit: u32,
+        prefetch: usize,
+    ) -> impl Stream<Item = Result<Vec<ChargesData>>> + '_ {
+        let first = params.page.unwrap_or(1);
+        let params = ListParams {
+            limit: Some(limit.max(1)),
+            ..params
+        };
+        stream::iter((first..).take(MAX_CHARGE_PAGES as usize))
+            .map(move |page| {
+                let params = ListParams {
+                    page: Some(page),
+                    ..params.clone()
+                };
+                async move { self.list_charges(Some(&params)).await }
+            })
+            .buffered(prefetch.max(1))
+            .scan(
+                (false, Vec::new()),
+                move |(done, previous): &mut (bool, Vec<String>), page| {
+                    if *done {
+                        return future::ready(None);
+                    }
+                    let page = match page {
+                        Ok(charges) => {
+                            let ids: Vec<String> =
+                                charges.iter().map(|charge| charge.id.clone()).collect();
+                            if !ids.is_empty() && ids == *previous {
+                                *done = true;
+                                return future::ready(None);
+                            }
+                            *done = charges.len() < limit.max(1) as usize;
+                            *previous = ids;
+                            Ok(charges)
+                        }
+                        Err(err) => {
+                            *done = true;
+                            Err(err)
+                        }
+                    };
+                    future::ready(Some(page))
+                },
+            )
+            .filter(|page| future::ready(!matches!(page, Ok(charges) if charges.is_empty())))
+    }
+
     /// Retrieves all information relating a specific Charge / Payment Request.
+    #[cfg(feature = "charges")]
     pub async fn get_charge<T>(&self, charge_id: T) -> Result<FetchOneChargeResponse>
     where
         T: AsRef<str>,
     {
-        let url = format!("{}/v0/charges/{}", &self.domain, charge_id.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::CHARGES, &[charge_id.as_ref()])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
+    /// Retrieves several Charges by id, running at most `concurrency` requests at a time.
+    /// Results are returned in the same order as `charge_ids`.
+    #[cfg(feature = "charges")]
+    pub async fn get_charges_by_ids<T>(
+        &self,
+        charge_ids: &[T],
+        concurrency: usize,
+    ) -> Vec<Result<ChargesData>>
+    where
+        T: AsRef<str>,
+    {
+        stream::iter(charge_ids)
+            .map(|charge_id| async move {
+                let charge_id = charge_id.as_ref();
+                self.get_charge(charge_id)
+                    .await?
+                    .data
+                    .ok_or_else(|| ErrorMsg::MissingData(charge_id.to_string()).into())
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
     /// Send Bitcoin payments directly to a user's ZBD Gamertag
+    #[cfg(feature = "gamertag")]
     pub async fn pay_gamertag(&self, payment: &GamertagPayment) -> Result<GamertagPayResponse> {
         payment
             .validate()
             .map_err(|e| ErrorMsg::BadGamerTagFormat(e.to_string()))?;
 
-        let url = format!("{}/v0/gamertag/send-payment", &self.domain);
+        let url = self.url(endpoints::GAMERTAG_SEND_PAYMENT, &[])?;
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
-
-        self.parse_response(resp).await
+        self.send_mutation(self.add_headers(self.reqw_cli.post(url)).json(payment))
+            .await
     }
 
     /// Create a bolt 11 invoice so you can pay a specified gamertag
+    #[cfg(feature = "gamertag")]
     pub async fn fetch_charge_from_gamertag(
         &self,
         payment: &GamertagPayment,
@@ -191,97 +872,95 @@ impl ZebedeeClient {
             .validate()
             .map_err(|e| ErrorMsg::BadPayloadData(e.to_string()))?;
 
-        let url = format!("{}/v0/gamertag/charges", &self.domain);
-
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
-
-        self.parse_response(resp).await
+        let url = self.url(endpoints::GAMERTAG_CHARGES, &[])?;
+        self.send_mutation(self.add_headers(self.reqw_cli.post(url)).json(payment))
+            .await
     }
 
     /// Get data on payments sent to ZBD Gamertags.
     /// The data payload returned will inform you of the status of that transaction as well as any associated fees.
+    #[cfg(feature = "gamertag")]
     pub async fn get_gamertag_tx<T>(&self, transaction_id: T) -> Result<GamertagTxResponse>
     where
         T: AsRef<str>,
     {
-        let url = format!(
-            "{}/v0/gamertag/transaction/{}",
-            &self.domain,
-            transaction_id.as_ref()
-        );
+        let url = self.url(endpoints::GAMERTAG_TRANSACTION, &[transaction_id.as_ref()])?;
 
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Get a given User's ID when provided with a ZBD Gamertag.
+    #[cfg(feature = "gamertag")]
     pub async fn get_userid_by_gamertag<T>(&self, gamertag: T) -> Result<IdFromGamertagResponse>
     where
         T: AsRef<str>,
     {
-        let url = format!("{}/v0/user-id/gamertag/{}", &self.domain, gamertag.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::USER_ID_GAMERTAG, &[gamertag.as_ref()])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Get a given user's ZBD Gamertag from user id
+    #[cfg(feature = "gamertag")]
     pub async fn get_gamertag_by_userid<T>(&self, user_id: T) -> Result<GamertagUserIdResponse>
     where
         T: AsRef<str>,
     {
-        let url = format!("{}/v0/gamertag/user-id/{}", &self.domain, user_id.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::GAMERTAG_USER_ID, &[user_id.as_ref()])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Initiates a transfer of funds between two Project Wallets you own.
+    #[cfg(feature = "internal_transfer")]
     pub async fn internal_transfer(
         &self,
         internal_transfer_payload: &InternalTransfer,
     ) -> Result<InternalTransferResponse> {
-        let url = format!("{}/v0/internal-transfer", &self.domain);
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(internal_transfer_payload)
-            .send()
-            .await?;
-
-        self.parse_response(resp).await
+        let url = self.url(endpoints::INTERNAL_TRANSFER, &[])?;
+        self.send_mutation(
+            self.add_headers(self.reqw_cli.post(url))
+                .json(internal_transfer_payload),
+        )
+        .await
     }
 
     /// Send Bitcoin payments directly to a Lightning Address.
+    #[cfg(feature = "ln_address")]
     pub async fn pay_ln_address(&self, payment: &LnPayment) -> Result<PayLnAddressResponse> {
-        let url = format!("{}/v0/ln-address/send-payment", &self.domain);
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
+        payment.validate()?;
 
-        self.parse_response(resp).await
+        let url = self.url(endpoints::LN_ADDRESS_SEND_PAYMENT, &[])?;
+        self.send_mutation(self.add_headers(self.reqw_cli.post(url)).json(payment))
+            .await
     }
 
     /// Create a Charge / Payment Request QR code for a Lightning Address
+    #[cfg(feature = "ln_address")]
     pub async fn fetch_charge_ln_address(
         &self,
         payment: &LnFetchCharge,
     ) -> Result<FetchLnChargeResponse> {
-        let url = format!("{}/v0/ln-address/fetch-charge", &self.domain);
+        payment.validate()?;
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(payment)
-            .send()
-            .await?;
+        let url = self.url(endpoints::LN_ADDRESS_FETCH_CHARGE, &[])?;
+        self.send_mutation(self.add_headers(self.reqw_cli.post(url)).json(payment))
+            .await
+    }
 
-        self.parse_response(resp).await
+    /// Fetches the public static charge (LNURL-pay metadata) behind a gamertag's Lightning Address
+    #[cfg(feature = "gamertag")]
+    pub async fn get_gamertag_static_charge<T>(&self, gamertag: T) -> Result<ValidateLnAddrResponse>
+    where
+        T: AsRef<str>,
+    {
+        self.validate_ln_address(&gamertag_ln_address(gamertag.as_ref()))
+            .await
     }
 
     /// Validate whether a user's entered Lightning Address is indeed a real Lightning Address
+    #[cfg(feature = "ln_address")]
     pub async fn validate_ln_address(
         &self,
         lightning_address: &LnAddress,
@@ -290,68 +969,72 @@ impl ZebedeeClient {
             ErrorMsg::BadLnAddress(lightning_address.address.clone(), e.to_string())
         })?;
 
-        let url = format!(
-            "{}/v0/ln-address/validate/{}",
-            &self.domain, &lightning_address.address
-        );
+        let url = self.url(
+            endpoints::LN_ADDRESS_VALIDATE,
+            &[&lightning_address.address],
+        )?;
 
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
 
         self.parse_response(resp).await
     }
 
     /// Pays a Charge / Payment Request in the Bitcoin Lightning Network
+    #[cfg(feature = "payments")]
     pub async fn pay_invoice(&self, payment: &Payment) -> Result<PaymentInvoiceResponse> {
-        let url = format!("{}/v0/payments", &self.domain);
+        payment.validate()?;
+        payment.check_amount()?;
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(&payment)
-            .send()
-            .await?;
+        let url = self.url(endpoints::PAYMENTS, &[])?;
 
-        self.parse_response(resp).await
+        self.send_mutation(self.add_headers(self.reqw_cli.post(url)).json(&payment))
+            .await
     }
 
+    #[cfg(feature = "payments")]
     pub async fn get_payments(&self) -> Result<FetchPaymentsResponse> {
-        let url = format!("{}/v0/payments", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::PAYMENTS, &[])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Retrieves all the information related to a specific Payment
+    #[cfg(feature = "payments")]
     pub async fn get_payment<T>(&self, payment_id: T) -> Result<FetchOnePaymentsResponse>
     where
         T: AsRef<str>,
     {
-        let url = format!("{}/v0/payments/{}", &self.domain, payment_id.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::PAYMENTS, &[payment_id.as_ref()])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Check if provided ip address will be [supported](https://zebedee.io/countries) by Zebedee REST API
+    #[cfg(feature = "utilities")]
     pub async fn get_is_supported_region_by_ip<T>(&self, ip: T) -> Result<SupportedIpResponse>
     where
         T: AsRef<str>,
     {
-        let url = format!("{}/v0/is-supported-region/{}", &self.domain, ip.as_ref());
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::IS_SUPPORTED_REGION, &[ip.as_ref()])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Check if callback response is from legit Zebedee ip address
+    #[cfg(feature = "utilities")]
     pub async fn get_prod_ips(&self) -> Result<ProdIpsResponse> {
-        let url = format!("{}/v0/prod-ips", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::PROD_IPS, &[])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Get the latest price for Bitcoin in US Dollars.
     /// The exchange rate feed is refreshed every 5 seconds and is based upon a combination of industry-leading
     /// partner exchange providers's price feeds.
+    #[cfg(feature = "utilities")]
     pub async fn get_btc_usd(&self) -> Result<BtcToUsdResponse> {
-        let url = format!("{}/v0/btcusd", &self.domain);
-        let resp = self.reqw_cli.get(&url).send().await?;
+        let url = self.url(endpoints::BTCUSD, &[])?;
+        let resp = self.send(self.reqw_cli.get(url)).await?;
         self.parse_response(resp).await
     }
 
@@ -362,28 +1045,77 @@ impl ZebedeeClient {
     /// `Charges`: Lightning QR codes that YOU SPEND
     /// ***
     /// `Withdrawal Requests`: Lightning QR codes that YOU RECEIVE
+    #[cfg(feature = "withdrawal_request")]
     pub async fn create_withdrawal_request(
         &self,
         withdrawal_request: &WithdrawalReqest,
     ) -> Result<CreateWithdrawalResponse> {
-        let url = format!("{}/v0/withdrawal-requests", &self.domain);
+        withdrawal_request.validate()?;
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .json(&withdrawal_request)
-            .send()
-            .await?;
+        let url = self.url(endpoints::WITHDRAWAL_REQUESTS, &[])?;
 
-        self.parse_response(resp).await
+        self.send_mutation(
+            self.add_headers(self.reqw_cli.post(url))
+                .json(&withdrawal_request),
+        )
+        .await
+    }
+
+    /// Returns the pending or completed Withdrawal Request with the same `internal_id`, creating
+    /// one only when there is none, so retried payouts don't hand out a second withdrawal link.
+    /// Expired and failed requests are ignored. Requests without an `internal_id` are always
+    /// created.
+    #[cfg(feature = "withdrawal_request")]
+    pub async fn get_or_create_withdrawal_request(
+        &self,
+        withdrawal_request: &WithdrawalReqest,
+    ) -> Result<CreateWithdrawalResponse> {
+        withdrawal_request.validate()?;
+
+        if !withdrawal_request.internal_id.is_empty() {
+            let existing = self
+                .get_withdrawal_requests(None)
+                .await?
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .find(|w| {
+                    w.internal_id == withdrawal_request.internal_id
+                        && matches!(
+                            w.status,
+                            models::WithdrawalStatus::Pending | models::WithdrawalStatus::Completed
+                        )
+                });
+            if let Some(existing) = existing {
+                return Ok(StdResp {
+                    success: true,
+                    data: Some(existing),
+                    message: None,
+                });
+            }
+        }
+
+        self.create_withdrawal_request(withdrawal_request).await
     }
 
-    pub async fn get_withdrawal_requests(&self) -> Result<FetchWithdrawalsResponse> {
-        let url = format!("{}/v0/withdrawal-requests", &self.domain);
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+    /// Retrieves all Withdrawal Requests, optionally narrowed down or paged with `params`.
+    #[cfg(feature = "withdrawal_request")]
+    pub async fn get_withdrawal_requests(
+        &self,
+        params: Option<&ListParams>,
+    ) -> Result<FetchWithdrawalsResponse> {
+        let url = self.url(endpoints::WITHDRAWAL_REQUESTS, &[])?;
+        let mut request_builder = self.add_headers(self.reqw_cli.get(url));
+        if let Some(params) = params {
+            request_builder = request_builder.query(params).query(&params.extra_params);
+        }
+
+        let resp = self.send(request_builder).await?;
         self.parse_response(resp).await
     }
 
     /// Retrieves details about a specific Withdrawal Request.
+    #[cfg(feature = "withdrawal_request")]
     pub async fn get_withdrawal_request<T>(
         &self,
         withdrawal_id: T,
@@ -391,37 +1123,35 @@ impl ZebedeeClient {
     where
         T: AsRef<str>,
     {
-        let url = format!(
-            "{}/v0/withdrawal-requests/{}",
-            &self.domain,
-            withdrawal_id.as_ref()
-        );
-        let resp = self.add_headers(self.reqw_cli.get(&url)).send().await?;
+        let url = self.url(endpoints::WITHDRAWAL_REQUESTS, &[withdrawal_id.as_ref()])?;
+        let resp = self.send(self.add_headers(self.reqw_cli.get(url))).await?;
         self.parse_response(resp).await
     }
 
     /// Send instant Bitcoin payments to any email.
+    #[cfg(feature = "email")]
     pub async fn pay_email(
         &self,
         email_payment_request: &EmailPaymentReqest,
     ) -> Result<EmailPaymentResponse> {
-        let url = format!("{}/v0/email/send-payment", &self.domain);
+        email_payment_request.validate()?;
 
-        let resp = self
-            .add_headers(self.reqw_cli.post(&url))
-            .header("Content-Type", "application/json")
-            .json(&email_payment_request)
-            .send()
-            .await?;
+        let url = self.url(endpoints::EMAIL_SEND_PAYMENT, &[])?;
 
-        self.parse_response(resp).await
+        self.send_mutation(
+            self.add_headers(self.reqw_cli.post(url))
+                .header("Content-Type", "application/json")
+                .json(&email_payment_request),
+        )
+        .await
     }
 
+    #[cfg(feature = "oauth")]
     pub async fn create_auth_url<T>(&self, challenge: T) -> Result<String>
     where
         T: AsRef<str>,
     {
-        let url = format!("{}/v1/oauth2/authorize", &self.domain);
+        let url = self.url(endpoints::OAUTH2_AUTHORIZE, &[])?;
 
         let auth_url = self
             .reqw_cli
@@ -444,6 +1174,7 @@ impl ZebedeeClient {
         Ok(auth_url)
     }
 
+    #[cfg(feature = "oauth")]
     pub async fn fetch_token<A, B>(&self, code: A, verifier: B) -> Result<FetchAccessTokenRes>
     where
         A: AsRef<str>,
@@ -452,20 +1183,22 @@ impl ZebedeeClient {
         let payload = FetchTokenBody::new(self, code.as_ref(), verifier.as_ref());
         payload.validate()?;
 
-        let url = format!("{}/v1/oauth2/token", &self.domain);
+        let url = self.url(endpoints::OAUTH2_TOKEN, &[])?;
 
         let resp = self
-            .reqw_cli
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.reqw_cli
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
-        self.parse_response(resp).await
+        self.parse_token_response(resp).await
     }
 
     /// In order to fetch a new accessToken for a given ZBD User, make sure to use the refreshToken using the token endpoint.
+    #[cfg(feature = "oauth")]
     pub async fn refresh_token<T>(&self, refresh_token: T) -> Result<FetchPostRes>
     where
         T: AsRef<str>,
@@ -473,59 +1206,67 @@ impl ZebedeeClient {
         let payload = FetchRefresh::new(self, refresh_token.as_ref());
         payload.validate()?;
 
-        let url = format!("{}/v1/oauth2/token", &self.domain);
+        let url = self.url(endpoints::OAUTH2_TOKEN, &[])?;
         let resp = self
-            .reqw_cli
-            .post(&url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
+            .send(
+                self.reqw_cli
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .json(&payload),
+            )
             .await?;
 
-        self.parse_response(resp).await
+        self.parse_token_response(resp).await
     }
 
     /// You can use this API endpoint to fetch information about a given ZBD User, granted you can pass the provided accessToken.
-
-    pub async fn fetch_user_data<T>(&self, token: T) -> Result<StdResp<ZBDUserData>>
+    #[cfg(feature = "oauth")]
+    pub async fn fetch_user_data<T>(&self, token: T) -> Result<StdResp<Option<ZBDUserData>>>
     where
         T: AsRef<str>,
     {
         //let mut token_header_string: String = "Bearer ".to_owned();
         //token_header_string.push_str(&bearer_token);
 
-        let url = format!("{}/v1/oauth2/user", &self.domain);
+        let url = self.url(endpoints::OAUTH2_USER, &[])?;
 
         let resp = self
-            .add_headers(self.reqw_cli.get(&url))
-            .header("usertoken", token.as_ref())
-            .send()
+            .send(
+                self.add_headers(self.reqw_cli.get(url))
+                    .header("usertoken", token.as_ref()),
+            )
             .await?;
 
         self.parse_response(resp).await
     }
 
     /// You can use this API endpoint to fetch information about a given ZBD User's Wallet, granted you can pass the provided accessToken.
-    pub async fn fetch_user_wallet_data<T>(&self, token: T) -> Result<StdResp<ZBDUserWalletData>>
+    #[cfg(feature = "oauth")]
+    pub async fn fetch_user_wallet_data<T>(
+        &self,
+        token: T,
+    ) -> Result<StdResp<Option<ZBDUserWalletData>>>
     where
         T: AsRef<str>,
     {
         //let mut token_header_string: String = "Bearer ".to_owned();
         //token_header_string.push_str(&bearer_token);
 
-        let url = format!("{}/v1/oauth2/wallet", &self.domain);
+        let url = self.url(endpoints::OAUTH2_WALLET, &[])?;
 
         let resp = self
-            .add_headers(self.reqw_cli.get(&url))
-            .header("usertoken", token.as_ref())
-            .send()
+            .send(
+                self.add_headers(self.reqw_cli.get(url))
+                    .header("usertoken", token.as_ref()),
+            )
             .await?;
 
         self.parse_response(resp).await
     }
 }
 
-#[derive(Default, Clone, Validate, Deserialize, Debug)]
+#[cfg(feature = "oauth")]
+#[derive(Default, Clone, Validate, Deserialize, Debug, PartialEq, Eq)]
 pub struct ZebedeeOauth {
     #[validate(length(equal = 36))]
     client_id: String,
@@ -538,6 +1279,7 @@ pub struct ZebedeeOauth {
     scope: String,
 }
 
+#[cfg(feature = "oauth")]
 impl ZebedeeOauth {
     fn new(
         client_id: String,
@@ -558,15 +1300,47 @@ impl ZebedeeOauth {
 
 impl Default for ZebedeeClient {
     fn default() -> Self {
-        ZebedeeClient {
-            domain: String::from("https://api.zebedee.io"),
-            reqw_cli: reqwest::Client::new(),
-            apikey: String::from("errornotset"),
-            oauth: Default::default(),
+        ZebedeeClient::with_reqw_cli(reqwest::Client::new(), Some((HttpVersion::Auto, None)))
+    }
+}
+
+/// HTTP protocol version used to talk to the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HttpVersion {
+    /// HTTP/2 when the server offers it during the TLS handshake, HTTP/1.1 otherwise
+    #[default]
+    Auto,
+    Http1Only,
+    /// HTTP/2 without negotiation, multiplexing concurrent requests over one connection.
+    /// Also works over plain HTTP, e.g. to a local gateway that speaks HTTP/2.
+    Http2PriorKnowledge,
+}
+
+/// Compares configuration only: the HTTP client, metrics and rate limit are ignored, so two clients
+/// built with the same key and domain are equal.
+impl PartialEq for ZebedeeClient {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "oauth")]
+        if self.oauth != other.oauth {
+            return false;
         }
+        self.domain == other.domain
+            && self.fallback_domain == other.fallback_domain
+            && self.apikey == other.apikey
+            && self.apikey_header == other.apikey_header
+            && self.locale == other.locale
+            && self.dry_run == other.dry_run
+            && self.max_body_size == other.max_body_size
+            && self.retry_policy == other.retry_policy
+            && self.timeout == other.timeout
+            && self.connect_timeout == other.connect_timeout
+            && self.http_version == other.http_version
     }
 }
 
+impl Eq for ZebedeeClient {}
+
+#[cfg(feature = "oauth")]
 #[derive(Clone, Debug, Validate, Deserialize)]
 pub struct PKCE {
     #[validate(length(equal = 43))]
@@ -575,6 +1349,7 @@ pub struct PKCE {
     pub challenge: String,
 }
 
+#[cfg(feature = "oauth")]
 impl PKCE {
     pub fn new(input: [u8; 32]) -> Self {
         let verifier = base64_url::encode(&input);
@@ -598,6 +1373,7 @@ impl PKCE {
     }
 }
 
+#[cfg(feature = "oauth")]
 impl From<&str> for PKCE {
     fn from(value: &str) -> Self {
         let mut hasher = Sha256::new();
@@ -611,9 +1387,70 @@ impl From<&str> for PKCE {
     }
 }
 
+/// The `success` flag of a response body, read on its own so every other field is skipped
+/// rather than parsed again.
+#[derive(Deserialize)]
+struct Outcome {
+    success: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StdResp<T> {
     pub success: bool,
     pub data: T,
     pub message: Option<String>,
 }
+
+impl<T> StdResp<Option<Vec<T>>> {
+    /// Returns `true` when a list response has no items, including when `data` is missing.
+    pub fn is_empty(&self) -> bool {
+        self.data.as_ref().is_none_or(Vec::is_empty)
+    }
+
+    /// Iterates over the listed items, none when `data` is missing.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.as_deref().unwrap_or_default().iter()
+    }
+}
+
+/// Fixtures for testing code that consumes responses, together with the `Default` impls the
+/// `mock` feature derives on response data.
+#[cfg(any(test, feature = "mock"))]
+impl<T> StdResp<Option<T>> {
+    /// A successful response carrying `data`.
+    pub fn ok(data: T) -> Self {
+        StdResp {
+            success: true,
+            data: Some(data),
+            message: None,
+        }
+    }
+
+    /// A failed response without data.
+    pub fn error<M: Into<String>>(message: M) -> Self {
+        StdResp {
+            success: false,
+            data: None,
+            message: Some(message.into()),
+        }
+    }
+}
+
+/// Yields the listed items, so a list response can be used directly in a `for` loop.
+impl<T> IntoIterator for StdResp<Option<Vec<T>>> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.unwrap_or_default().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a StdResp<Option<Vec<T>>> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
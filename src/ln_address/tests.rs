@@ -1,4 +1,8 @@
-use crate::ZebedeeClient;
+use crate::{
+    errors::ZebedeeError,
+    test_utils::{MockResponse, MockServer},
+    ZebedeeClient,
+};
 use std::env;
 
 use super::*;
@@ -13,6 +17,7 @@ async fn test_pay_ln_address() {
         ln_address: String::from("miketwenty1@zbd.gg"),
         amount: String::from("1000"),
         comment: "rust sdk ln address rest".to_string(),
+        ..Default::default()
     };
     let r = zebedee_client
         .pay_ln_address(payment)
@@ -68,3 +73,77 @@ async fn test_validate_address_format() {
 
     assert_eq!(ln_address.validate(), Ok(()));
 }
+
+#[tokio::test]
+async fn test_pay_ln_address_validation() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":null,"message":"Payment done."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let payment = LnPayment {
+        ln_address: String::from("not an address"),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let r = zebedee_client.pay_ln_address(&payment).await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Validate(_)));
+    assert!(server.requests.lock().unwrap().is_empty());
+
+    let payment = LnPayment {
+        ln_address: String::from("satoshi@zbd.gg"),
+        amount: String::from("1000"),
+        internal_id: String::from("order-7"),
+        ..Default::default()
+    };
+    zebedee_client.pay_ln_address(&payment).await.unwrap();
+    assert!(server.requests.lock().unwrap()[0].contains(r#""internalId":"order-7""#));
+}
+
+#[tokio::test]
+async fn test_fetch_charge_ln_address_dry_run() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":null}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .dry_run(true)
+        .build();
+
+    let charge = LnFetchCharge {
+        ln_address: String::from("satoshi@zbd.gg"),
+        amount: String::from("1000"),
+        ..Default::default()
+    };
+    let r = zebedee_client
+        .fetch_charge_ln_address(&charge)
+        .await
+        .unwrap();
+    assert!(r.success);
+    assert!(r.data.is_none());
+    assert!(server.requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ln_payment_wire_format() {
+    let payment = LnPayment {
+        ln_address: String::from("andre@zbd.gg"),
+        amount: String::from("5000"),
+        comment: String::from("tip"),
+        internal_id: String::from("order-1"),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&payment).unwrap(),
+        serde_json::json!({
+            "lnAddress": "andre@zbd.gg",
+            "amount": "5000",
+            "comment": "tip",
+            "internalId": "order-1",
+        })
+    );
+}
@@ -1,9 +1,12 @@
-use crate::StdResp;
+use crate::{custom_deserializer::datetime_field, StdResp};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use validator::{Validate, ValidationErrors};
 
+datetime_field!(deserialize_created_at, "createdAt");
+datetime_field!(deserialize_processed_at, "processedAt");
+
 pub type PayLnAddressResponse = StdResp<Option<LnSendPaymentData>>;
 pub type FetchLnChargeResponse = StdResp<Option<LnFetchChargeData>>;
 pub type ValidateLnAddrResponse = StdResp<Option<LnValidateData>>;
@@ -23,40 +26,42 @@ impl LnAddress {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct LnPayerData {
     pub name: HashMap<String, bool>,
     pub identifier: HashMap<String, bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct LnValidateMetadata {
-    #[serde(rename = "minSendable")]
     pub min_sendable: Option<u64>,
-    #[serde(rename = "maxSendable")]
     pub max_sendable: Option<u64>,
-    #[serde(rename = "commentAllowed")]
     pub comment_allowed: Option<u64>,
     pub tag: Option<String>,
     pub metadata: Option<String>,
     pub callback: Option<String>,
-    #[serde(rename = "payerData")]
     pub payer_data: Option<LnPayerData>,
     pub disposable: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct LnValidateData {
     pub valid: bool,
     pub metadata: LnValidateMetadata,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct LnInvoice {
     pub uri: String,
     pub request: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct LnFetchChargeData {
     #[serde(rename = "lnaddress")]
     pub ln_address: String,
@@ -65,6 +70,8 @@ pub struct LnFetchChargeData {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct LnSendPaymentData {
     pub id: String,
     pub fee: Option<String>,
@@ -73,27 +80,29 @@ pub struct LnSendPaymentData {
     pub preimage: Option<String>,
     pub status: String,
     pub invoice: String,
-    #[serde(rename = "walletId")]
     pub wallet_id: String,
-    #[serde(rename = "transactionId")]
     pub transaction_id: String,
-    #[serde(rename = "createdAt")]
+    #[serde(deserialize_with = "deserialize_created_at")]
     pub created_at: DateTime<Utc>,
-    #[serde(rename = "processedAt")]
+    #[serde(deserialize_with = "deserialize_processed_at")]
     pub processed_at: DateTime<Utc>,
     #[serde(rename = "callbackURL")]
     pub callback_url: Option<String>,
-    #[serde(rename = "internalId")]
     pub internal_id: Option<String>,
 }
 
 /// Use this struct to create a well crafted json body for your Lightning Address payments
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct LnPayment {
-    #[serde(rename = "lnAddress")]
+    #[validate(email(message = "must be a name@domain Lightning Address"))]
     pub ln_address: String,
     pub amount: String,
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub comment: String,
+    /// Echoed back on the payment's `internal_id`, for linking it to your own ledger
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub internal_id: String,
 }
 
 impl Default for LnPayment {
@@ -102,16 +111,18 @@ impl Default for LnPayment {
             ln_address: String::from(""),
             amount: String::from(""),
             comment: String::from("using zebedee rust sdk"),
+            internal_id: String::from(""),
         }
     }
 }
 
 /// Use this struct to create a well crafted json body for creating charges for Ligthning Addresses
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct LnFetchCharge {
     #[serde(rename = "lnaddress")]
     pub ln_address: String,
     pub amount: String,
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub description: String,
 }
 
@@ -0,0 +1,110 @@
+use serde::{ser::Error as _, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Wire names of the fields masked by [`Redact::redacted`]: invoice strings, callback urls,
+/// which may carry a callback secret, and the caller's own correlation ids.
+pub const DEFAULT_REDACTED_FIELDS: &[&str] =
+    &["callbackUrl", "invoice", "internalId", "request", "uri"];
+
+const MASK: &str = "[redacted]";
+
+/// Renders a value as JSON with snake_case keys for internal logs, independent of the
+/// camelCase wire format sent to the API.
+pub trait LogJson {
+    fn to_log_json(&self) -> serde_json::Result<Value>;
+}
+
+impl<T: Serialize> LogJson for T {
+    fn to_log_json(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self).map(snake_case_keys)
+    }
+}
+
+/// Gives any serializable value a view that masks selected fields, so audit logs can record
+/// the shape of a request without the invoice or internal id in plaintext.
+pub trait Redact: Serialize + Sized {
+    /// Masks [`DEFAULT_REDACTED_FIELDS`], use [`Redacted::fields`] to pick others.
+    fn redacted(&self) -> Redacted<'_, Self> {
+        Redacted {
+            value: self,
+            fields: DEFAULT_REDACTED_FIELDS,
+        }
+    }
+}
+
+impl<T: Serialize> Redact for T {}
+
+/// Serializes the wrapped value with the values of the configured fields replaced by
+/// `"[redacted]"`, at any depth. Field names are matched against the serialized (wire) names,
+/// and `null` values are kept so optional fields still show as unset.
+#[derive(Debug, Clone, Copy)]
+pub struct Redacted<'a, T> {
+    value: &'a T,
+    fields: &'a [&'a str],
+}
+
+impl<'a, T> Redacted<'a, T> {
+    pub fn fields(self, fields: &'a [&'a str]) -> Self {
+        Redacted { fields, ..self }
+    }
+}
+
+impl<T: Serialize> Serialize for Redacted<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = serde_json::to_value(self.value).map_err(S::Error::custom)?;
+        mask_fields(value, self.fields).serialize(serializer)
+    }
+}
+
+fn mask_fields(value: Value, fields: &[&str]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| {
+                    let v = if fields.contains(&k.as_str()) && !v.is_null() {
+                        Value::String(String::from(MASK))
+                    } else {
+                        mask_fields(v, fields)
+                    };
+                    (k, v)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(|v| mask_fields(v, fields)).collect())
+        }
+        other => other,
+    }
+}
+
+fn snake_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (snake_case(&k), snake_case_keys(v)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(snake_case_keys).collect()),
+        other => other,
+    }
+}
+
+/// `callbackUrl` and `callbackURL` both become `callback_url`
+fn snake_case(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut out = String::with_capacity(key.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
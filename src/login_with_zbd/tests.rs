@@ -1,5 +1,11 @@
-use crate::ZebedeeClient;
+use super::*;
 use crate::PKCE;
+use crate::{
+    errors::{ErrorMsg, ZebedeeError},
+    test_utils::{MockResponse, MockServer},
+    ZebedeeClient,
+};
+use chrono::{Duration, Utc};
 use std::env;
 
 #[tokio::test]
@@ -174,3 +180,126 @@ async fn test_fetch_user_wallet_data() {
     };
     assert!(i.is_empty());
 }
+
+#[tokio::test]
+async fn test_needs_reauthorization() {
+    let token = FetchAccessTokenRes {
+        access_token: String::from("access"),
+        token_type: String::from("Bearer"),
+        expires_in: 3600,
+        refresh_token: String::from("refresh"),
+        refresh_token_expires_in: 86400,
+        scope: String::from("user"),
+    };
+
+    let fetched_at = Utc::now();
+    assert_eq!(
+        token.access_token_expires_at(fetched_at),
+        fetched_at + Duration::hours(1)
+    );
+    assert!(!token.needs_reauthorization(fetched_at, Duration::hours(1)));
+
+    let fetched_at = Utc::now() - Duration::hours(23);
+    assert!(token.needs_reauthorization(fetched_at, Duration::hours(1)));
+}
+
+#[tokio::test]
+async fn test_user_data_missing_optional_fields() {
+    let body = r#"{
+        "id": "d0f3f5c2-4b1a-4b8e-9a43-2c1b5a5f1e7a",
+        "email": "satoshi@zebedee.io",
+        "gamertag": "satoshi",
+        "isVerified": true,
+        "lightningAddress": "satoshi@zbd.gg"
+    }"#;
+
+    let user: ZBDUserData = serde_json::from_str(body).unwrap();
+    assert!(user.image.is_none());
+    assert!(user.public_bio.is_empty());
+}
+
+#[tokio::test]
+async fn test_user_data_lightning_address() {
+    let body = r#"{
+        "id": "d0f3f5c2-4b1a-4b8e-9a43-2c1b5a5f1e7a",
+        "email": "satoshi@zebedee.io",
+        "gamertag": "satoshi",
+        "isVerified": true,
+        "lightningAddress": "satoshi@zbd.gg"
+    }"#;
+
+    let mut user: ZBDUserData = serde_json::from_str(body).unwrap();
+    let address = user.parse_lightning_address().unwrap();
+    assert_eq!(address.name, "satoshi");
+    assert_eq!(address.domain, "zbd.gg");
+    assert_eq!(address.to_string(), "satoshi@zbd.gg");
+
+    for bad in ["", "satoshi", "@zbd.gg", "satoshi@zbd", "satoshi@zbd.gg."] {
+        user.lightning_address = String::from(bad);
+        assert!(matches!(
+            user.parse_lightning_address(),
+            Err(ErrorMsg::BadLnAddress(ref address, _)) if address == bad
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_wallet_exceeded_limit() {
+    let wallet = ZBDUserWalletData {
+        balance: String::from("500000"),
+        remaining_amount_limits: ZBDUserWalletDataLimits {
+            daily: String::from("10000"),
+            max_credit: String::from("1000000"),
+            monthly: String::from("100000"),
+            weekly: String::from("5000"),
+        },
+    };
+
+    assert_eq!(wallet.exceeded_limit(4000), None);
+    assert_eq!(wallet.exceeded_limit(6000), Some(WalletLimit::Weekly));
+    assert_eq!(wallet.exceeded_limit(20000), Some(WalletLimit::Daily));
+}
+
+#[tokio::test]
+async fn test_fetch_token_oauth_error() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            400,
+            r#"{"error":"invalid_grant","error_description":"authorization code expired"}"#,
+        ),
+        MockResponse::json(401, r#"{"error":"invalid_client"}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .oauth(
+            String::from("c2f1a0b5-7e33-4f7a-9d2b-6c1e8f0a4b11"),
+            String::from("5d0e8b2a-1f4c-4a7e-b3d9-0c6f2e8a1d77"),
+            String::from("https://example.com/callback"),
+            String::from("0a9b8c7d-6e5f-4a3b-2c1d-0e9f8a7b6c5d"),
+            String::from("user"),
+        )
+        .build();
+
+    let code = "7f3c2b1a-9e8d-4c7b-a6f5-e4d3c2b1a098";
+    let verifier = PKCE::from("hellomynameiswhat").verifier;
+    let r = zebedee_client
+        .fetch_token(code, verifier)
+        .await
+        .err()
+        .unwrap();
+    let ZebedeeError::Oauth(err) = r else {
+        panic!("expected an oauth error, got {r:?}");
+    };
+    assert_eq!(err.error, "invalid_grant");
+    assert_eq!(err.to_string(), "invalid_grant: authorization code expired");
+
+    let r = zebedee_client
+        .refresh_token("3e2d1c0b-a9f8-4e7d-8c6b-5a4f3e2d1c0b")
+        .await
+        .err()
+        .unwrap();
+    assert!(
+        matches!(r, ZebedeeError::Oauth(ref e) if e.error == "invalid_client" && e.description.is_none())
+    );
+}
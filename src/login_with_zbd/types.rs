@@ -1,9 +1,11 @@
-use crate::ZebedeeClient;
+use crate::{destination::LightningAddress, errors::ErrorMsg, ZebedeeClient};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use validator::Validate;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct FetchPostRes {
     pub access_token: String,
     pub token_type: String,
@@ -83,6 +85,7 @@ impl<'a> FetchTokenBody<'a> {
 // }
 
 #[derive(Serialize, Validate, Deserialize, Debug)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct FetchAccessTokenRes {
     pub access_token: String,
     pub token_type: String,
@@ -92,6 +95,25 @@ pub struct FetchAccessTokenRes {
     pub scope: String,
 }
 
+impl FetchAccessTokenRes {
+    /// When the access token stops working, given the time the token was fetched.
+    pub fn access_token_expires_at(&self, fetched_at: DateTime<Utc>) -> DateTime<Utc> {
+        fetched_at + Duration::seconds(i64::from(self.expires_in))
+    }
+
+    /// When the refresh token stops working, given the time the token was fetched.
+    /// After this a silent refresh is impossible and the user has to log in again.
+    pub fn refresh_token_expires_at(&self, fetched_at: DateTime<Utc>) -> DateTime<Utc> {
+        fetched_at + Duration::seconds(i64::from(self.refresh_token_expires_in))
+    }
+
+    /// Returns `true` when the refresh token expires within `margin`, meaning the user should be
+    /// sent through the full authorization flow again rather than relying on `refresh_token`.
+    pub fn needs_reauthorization(&self, fetched_at: DateTime<Utc>, margin: Duration) -> bool {
+        Utc::now() + margin >= self.refresh_token_expires_at(fetched_at)
+    }
+}
+
 /// Use this struct to create a well crafted json body for token refreshes with ZBD Oauth
 #[derive(Serialize, Validate, Deserialize, Debug)]
 pub struct FetchRefresh<'a> {
@@ -123,33 +145,71 @@ impl<'a> FetchRefresh<'a> {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct ZBDUserData {
     pub id: String,
     pub email: String,
     pub gamertag: String,
     pub image: Option<String>,
-    #[serde(rename = "isVerified")]
     pub is_verified: bool,
-    #[serde(rename = "lightningAddress")]
     pub lightning_address: String,
-    #[serde(rename = "publicBio")]
+    #[serde(default)]
     pub public_bio: String,
-    #[serde(rename = "publicStaticCharge")]
+    #[serde(default)]
     pub public_static_charge: String,
 }
 
+impl ZBDUserData {
+    /// Validates `lightning_address` and splits it into name and domain.
+    pub fn parse_lightning_address(&self) -> Result<LightningAddress, ErrorMsg> {
+        self.lightning_address.parse()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct ZBDUserWalletData {
     pub balance: String,
-    #[serde(rename = "remainingAmountLimits")]
     pub remaining_amount_limits: ZBDUserWalletDataLimits,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct ZBDUserWalletDataLimits {
     pub daily: String,
-    #[serde(rename = "maxCredit")]
     pub max_credit: String,
     pub monthly: String,
     pub weekly: String,
 }
+
+/// Spending limit that a payment would exceed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletLimit {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl ZBDUserWalletData {
+    /// Returns the first remaining daily/weekly/monthly limit that a payment of
+    /// `amount` msats would exceed, or `None` if it fits within all of them.
+    /// Limits that fail to parse are ignored.
+    pub fn exceeded_limit(&self, amount: u64) -> Option<WalletLimit> {
+        let limits = &self.remaining_amount_limits;
+        [
+            (WalletLimit::Daily, &limits.daily),
+            (WalletLimit::Weekly, &limits.weekly),
+            (WalletLimit::Monthly, &limits.monthly),
+        ]
+        .into_iter()
+        .find(|(_, remaining)| {
+            remaining
+                .parse::<u64>()
+                .is_ok_and(|remaining| amount > remaining)
+        })
+        .map(|(limit, _)| limit)
+    }
+}
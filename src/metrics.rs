@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Request counters for a [`ZebedeeClient`](crate::ZebedeeClient), shared by all of its clones.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    requests: AtomicU64,
+    transport_errors: AtomicU64,
+    client_errors: AtomicU64,
+    server_errors: AtomicU64,
+    retries: AtomicU64,
+}
+
+impl Metrics {
+    /// Total requests sent
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Requests that failed before a response was received (timeouts, connection failures)
+    pub fn transport_errors(&self) -> u64 {
+        self.transport_errors.load(Ordering::Relaxed)
+    }
+
+    /// Responses with a 4xx status
+    pub fn client_errors(&self) -> u64 {
+        self.client_errors.load(Ordering::Relaxed)
+    }
+
+    /// Responses with a 5xx status
+    pub fn server_errors(&self) -> u64 {
+        self.server_errors.load(Ordering::Relaxed)
+    }
+
+    /// Requests resent after a timeout, also counted in `requests`
+    pub fn retries(&self) -> u64 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_transport_error(&self) {
+        self.transport_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_status(&self, status: reqwest::StatusCode) {
+        if status.is_client_error() {
+            self.client_errors.fetch_add(1, Ordering::Relaxed);
+        } else if status.is_server_error() {
+            self.server_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
@@ -1,9 +1,383 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt::Display;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
 pub enum UnitType {
+    /// The API's default unit
+    #[default]
     #[serde(rename = "msats")]
     Msats,
     #[serde(rename = "sats")]
     Sats,
+    #[serde(rename = "usd")]
+    Usd,
+}
+
+impl UnitType {
+    /// The unit as the API writes it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitType::Msats => "msats",
+            UnitType::Sats => "sats",
+            UnitType::Usd => "usd",
+        }
+    }
+}
+
+impl std::str::FromStr for UnitType {
+    type Err = crate::errors::ErrorMsg;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "msats" => Ok(UnitType::Msats),
+            "sats" => Ok(UnitType::Sats),
+            "usd" => Ok(UnitType::Usd),
+            other => Err(crate::errors::ErrorMsg::BadPayloadData(format!(
+                "unknown unit {other}"
+            ))),
+        }
+    }
+}
+
+/// An amount together with its unit, so msats, sats and US cents can't be mixed up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Amount {
+    /// In msats, sats or cents depending on `unit`
+    pub value: u64,
+    pub unit: UnitType,
+}
+
+impl Amount {
+    /// Reads an API `amount` and `unit` pair, see [`parse_amount`].
+    /// `None` when either doesn't parse.
+    pub fn parse(amount: &str, unit: &str) -> Option<Self> {
+        let unit = unit.parse().ok()?;
+        Some(Amount {
+            value: parse_amount(amount, &unit)?,
+            unit,
+        })
+    }
+
+    /// The amount in msats, `None` for USD amounts which need an exchange rate.
+    pub fn to_msats(&self) -> Option<u64> {
+        match self.unit {
+            UnitType::Msats => Some(self.value),
+            UnitType::Sats => self.value.checked_mul(1000),
+            UnitType::Usd => None,
+        }
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_amount(&self.value.to_string(), &self.unit))
+    }
+}
+
+/// An amount in millisatoshis, the API's default unit. Sent as the string form the API expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Msats(pub u64);
+
+impl Msats {
+    pub fn from_sats(sats: u64) -> Self {
+        Msats(sats.saturating_mul(1000))
+    }
+
+    /// Converts a bitcoin value into msats, rounding to the nearest msat.
+    pub fn from_btc(btc: f64) -> Self {
+        Msats((btc * 100_000_000_000.0).round().max(0.0) as u64)
+    }
+}
+
+impl From<u64> for Msats {
+    fn from(value: u64) -> Self {
+        Msats(value)
+    }
+}
+
+impl Serialize for Msats {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl Display for Msats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format_amount(&self.0.to_string(), &UnitType::Msats))
+    }
+}
+
+/// A US Dollar amount expressed in cents, used for USD-denominated charges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct UsdAmount(pub u64);
+
+impl UsdAmount {
+    /// Converts a dollar value into cents, rounding to the nearest cent.
+    pub fn from_dollars(dollars: f64) -> Self {
+        UsdAmount((dollars * 100.0).round().max(0.0) as u64)
+    }
+
+    pub fn cents(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for UsdAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "${}.{:02}", self.0 / 100, self.0 % 100)
+    }
+}
+
+/// Renders an API amount for display according to its unit: msats and sats as
+/// `"1,234 sats"`, USD cents as `"$12.34"`. Amounts that don't parse are returned unchanged.
+pub fn format_amount(amount: &str, unit: &UnitType) -> String {
+    let Some(value) = parse_amount(amount, unit) else {
+        return amount.to_string();
+    };
+    match unit {
+        UnitType::Msats => {
+            let sats = group_thousands(value / 1000);
+            match value % 1000 {
+                0 => format!("{sats} {}", if value == 1000 { "sat" } else { "sats" }),
+                msats => {
+                    let fraction = format!("{msats:03}");
+                    format!("{sats}.{} sats", fraction.trim_end_matches('0'))
+                }
+            }
+        }
+        UnitType::Sats => {
+            format!(
+                "{} {}",
+                group_thousands(value),
+                if value == 1 { "sat" } else { "sats" }
+            )
+        }
+        UnitType::Usd => format!("${}.{:02}", group_thousands(value / 100), value % 100),
+    }
+}
+
+/// Parses an API amount string according to its `unit`, into msats, sats or US cents.
+/// Thousands separators are ignored. USD amounts are whole cents, as the API sends them, so
+/// `"12.00"` is rejected rather than read as dollars; msats and sats only allow a fraction of
+/// zeros. Returns `None` for anything else.
+pub fn parse_amount(amount: &str, unit: &UnitType) -> Option<u64> {
+    let cleaned: String = amount
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ',' | '_' | ' '))
+        .collect();
+    let (whole, fraction) = match cleaned.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (cleaned.as_str(), None),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if whole.is_empty() || !is_digits(whole) || !fraction.is_none_or(is_digits) {
+        return None;
+    }
+    let whole = whole.parse::<u64>().ok()?;
+
+    match (unit, fraction) {
+        (_, None) => Some(whole),
+        (UnitType::Usd, Some(_)) => None,
+        (_, Some(fraction)) => fraction.bytes().all(|b| b == b'0').then_some(whole),
+    }
+}
+
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Query parameters for the list endpoints. Unset fields are left out of the query string.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListParams {
+    /// Page to return, starting from 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    /// Items per page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    /// Additional query parameters passed through as-is, for filters the API supports
+    /// before this crate models them.
+    #[serde(skip)]
+    pub extra_params: Vec<(String, String)>,
+}
+
+impl ListParams {
+    /// Appends a raw query parameter, see `extra_params`.
+    pub fn param<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.extra_params.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// Checks that the API echoed back the `internal_id` that was sent. An empty `sent` id is
+/// never checked since there is nothing to correlate.
+#[cfg(any(
+    feature = "charges",
+    feature = "payments",
+    feature = "withdrawal_request"
+))]
+pub(crate) fn verify_internal_id(
+    sent: &str,
+    received: Option<&str>,
+) -> Result<(), crate::errors::ErrorMsg> {
+    match received {
+        _ if sent.is_empty() => Ok(()),
+        Some(received) if received == sent => Ok(()),
+        received => Err(crate::errors::ErrorMsg::InternalIdMismatch(
+            sent.to_string(),
+            received.unwrap_or_default().to_string(),
+        )),
+    }
+}
+
+/// A random (version 4) UUID for requests created without an `internal_id` of their own.
+#[cfg(any(feature = "charges", feature = "withdrawal_request"))]
+pub(crate) fn generate_internal_id() -> String {
+    let mut bytes: [u8; 16] = rand::random();
+    // version 4 and the RFC 4122 variant, as laid out in section 4.4 of the RFC
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Defines a status enum that round trips through its API string, keeping statuses this crate
+/// doesn't know yet in an `Unknown` variant instead of failing to deserialize.
+macro_rules! status_enum {
+    ($(#[$meta:meta])* $name:ident { $($(#[$vmeta:meta])* $variant:ident => $value:literal,)+ }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(from = "String", into = "String")]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)+
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $value,)+
+                    $name::Unknown(status) => status,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                match value {
+                    $($value => $name::$variant,)+
+                    other => $name::Unknown(other.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                $name::from(value.as_str())
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                match value {
+                    $name::Unknown(status) => status,
+                    known => known.as_str().to_string(),
+                }
+            }
+        }
+
+        /// Never fails, unrecognised statuses parse to `Unknown`.
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($name::from(s))
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.as_str() == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.as_str() == *other
+            }
+        }
+    };
+}
+
+status_enum! {
+    /// Status of a Charge or of the callback announcing a change to it.
+    #[derive(Default)]
+    ChargeStatus {
+        #[default]
+        Pending => "pending",
+        Completed => "completed",
+        Expired => "expired",
+        Error => "error",
+    }
+}
+
+status_enum! {
+    /// Status of an outgoing Payment.
+    PaymentStatus {
+        Initialized => "initialized",
+        Pending => "pending",
+        Processing => "processing",
+        Completed => "completed",
+        Failed => "failed",
+        Error => "error",
+    }
+}
+
+status_enum! {
+    /// Status of a Withdrawal Request.
+    #[derive(Default)]
+    WithdrawalStatus {
+        #[default]
+        Pending => "pending",
+        Completed => "completed",
+        Expired => "expired",
+        Error => "error",
+    }
+}
+
+impl PaymentStatus {
+    /// Whether the payment is still in flight and may yet change status.
+    pub fn is_in_flight(&self) -> bool {
+        matches!(
+            self,
+            PaymentStatus::Initialized | PaymentStatus::Pending | PaymentStatus::Processing
+        )
+    }
 }
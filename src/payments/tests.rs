@@ -1,6 +1,11 @@
 use super::*;
-use crate::ZebedeeClient;
-use std::env;
+use crate::{
+    errors::{ErrorMsg, ZebedeeError},
+    models::PaymentStatus,
+    test_utils::{MockResponse, MockServer},
+    ZebedeeClient,
+};
+use std::{env, time::Duration};
 
 #[tokio::test]
 async fn test_pay_invoice() {
@@ -42,3 +47,151 @@ async fn test_get_payment() {
     let r = zebedee_client.get_payment(&payment_id).await.err().unwrap();
     assert!(r.to_string().contains("No Payment"));
 }
+
+const FIXED_INVOICE: &str = "lnbc120n1p0tdjwmpp5ycws0d788cjeqp9rn2wwxfymrekj9n80wy2yrk66tuu3ga5wukfsdzq2pshjmt9de6zqen0wgsrzv3qwp5hsetvwvsxzapqwdshgmmndp5hxtnsd3skxefwxqzjccqp2sp5vnsvmjlu6hrfegcdjs47njrga36g3x45wfmqjjjlerwgagj62yysrzjq2v4aw4gy7m93en32dcaplym056zezcljdjshyk8yakwtsp2h4yvcz9atuqqhtsqqqqqqqlgqqqqqqgqjq9qy9qsqhykfacrdy06cuyegvt4p50su53qwgrqn5jf6d83fd0upsa4frpxqnm2zl323zuvmz5ypv9gh9nr3jav6u2ccwkpd56h3n6l3ja5q7wgpxudlv4";
+const AMOUNTLESS_INVOICE: &str = "lnbc1p0tdjwmpp5ycws0d788cjeqp9rn2wwxfymrekj9n80wy2yrk66tuu3ga5wukfsdzq2pshjmt9de6zqen0wgsrzv3qwp5hsetvwvsxzapqwdshgmmndp5hxtnsd3skxefwxqzjccqp2sp5vnsvmjlu6hrfegcdjs47njrga36g3x45wfmqjjjlerwgagj62yysrzjq2v4aw4gy7m93en32dcaplym056zezcljdjshyk8yakwtsp2h4yvcz9atuqqhtsqqqqqqqlgqqqqqqgqjq9qy9qsqhykfacrdy06cuyegvt4p50su53qwgrqn5jf6d83fd0upsa4frpxqnm2zl323zuvmz5ypv9gh9nr3jav6u2ccwkpd56h3n6l3ja5q7wgp08xkxw";
+
+#[tokio::test]
+async fn test_pay_invoice_amount_check() {
+    let zebedee_client = ZebedeeClient::new().build();
+
+    let payment = Payment {
+        invoice: String::from(AMOUNTLESS_INVOICE),
+        ..Default::default()
+    };
+    let r = zebedee_client.pay_invoice(&payment).await.err().unwrap();
+    assert!(matches!(
+        r,
+        ZebedeeError::Msg(ErrorMsg::BadPaymentAmount(_))
+    ));
+
+    let payment = Payment {
+        invoice: String::from(FIXED_INVOICE),
+        amount: Some(String::from("13000")),
+        ..Default::default()
+    };
+    let r = zebedee_client.pay_invoice(&payment).await.err().unwrap();
+    assert!(matches!(
+        r,
+        ZebedeeError::Msg(ErrorMsg::BadPaymentAmount(ref msg)) if msg.contains("12000 msats")
+    ));
+
+    // repeating the invoice's own amount is harmless
+    let payment = Payment {
+        invoice: String::from(FIXED_INVOICE),
+        amount: Some(String::from("12000")),
+        ..Default::default()
+    };
+    assert!(payment.check_amount().is_ok());
+
+    let payment = Payment {
+        invoice: String::from(AMOUNTLESS_INVOICE),
+        amount: Some(String::from("12000")),
+        ..Default::default()
+    };
+    assert!(payment.check_amount().is_ok());
+}
+
+#[tokio::test]
+async fn test_wait_for_payment() {
+    let pending = r#"{"success":true,"data":{"id":"p1","unit":"msats","amount":"1000","description":"","status":"pending"}}"#;
+    let completed = r#"{"success":true,"data":{"id":"p1","unit":"msats","amount":"1000","description":"","status":"completed","preimage":"a1b2"}}"#;
+    let server = MockServer::start(vec![
+        MockResponse::json(200, pending),
+        MockResponse::json(200, completed),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client
+        .wait_for_payment(
+            "p1",
+            Duration::from_millis(10),
+            Duration::from_secs(5),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(r.status, Some(PaymentStatus::Completed));
+    assert_eq!(r.preimage.as_deref(), Some("a1b2"));
+}
+
+#[tokio::test]
+async fn test_pay_invoice_internal_id_round_trip() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"id":"p1","unit":"msats","amount":"12000","description":"","status":"pending","internalId":"ledger-42"}}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let payment = Payment {
+        invoice: String::from(FIXED_INVOICE),
+        internal_id: String::from("ledger-42"),
+        ..Default::default()
+    };
+    let r = zebedee_client
+        .pay_invoice(&payment)
+        .await
+        .unwrap()
+        .data
+        .unwrap();
+
+    assert!(server.requests.lock().unwrap()[0].contains(r#""internalId":"ledger-42""#));
+    assert_eq!(r.internal_id.as_deref(), Some("ledger-42"));
+    assert!(r.verify_internal_id(&payment.internal_id).is_ok());
+}
+
+#[tokio::test]
+async fn test_fee_ratio() {
+    let payment: PaymentsData = serde_json::from_str(
+        r#"{"id":"p1","unit":"msats","amount":"100000","fee":"500","description":""}"#,
+    )
+    .unwrap();
+    assert_eq!(payment.fee_ratio(), 0.005);
+    assert!(payment.is_fee_reasonable(5_000));
+    assert!(!payment.is_fee_reasonable(1_000));
+
+    let payment: PaymentsData =
+        serde_json::from_str(r#"{"id":"p2","unit":"msats","amount":"100000","description":""}"#)
+            .unwrap();
+    assert_eq!(payment.fee_ratio(), 0.0);
+    assert!(payment.is_fee_reasonable(0));
+}
+
+#[tokio::test]
+async fn test_payment_wire_format() {
+    let payment = Payment {
+        description: String::from("coffee"),
+        internal_id: String::from("order-1"),
+        invoice: String::from("lnbc1"),
+        amount: Some(String::from("5000")),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&payment).unwrap(),
+        serde_json::json!({
+            "description": "coffee",
+            "internalId": "order-1",
+            "invoice": "lnbc1",
+            "amount": "5000",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_mock_fixtures() {
+    let resp = PaymentInvoiceResponse::ok(PaymentsData {
+        id: String::from("p1"),
+        amount: String::from("1000"),
+        unit: String::from("msats"),
+        status: Some(PaymentStatus::Completed),
+        ..Default::default()
+    });
+    assert!(resp.success);
+    assert_eq!(resp.data.unwrap().amount().unwrap().to_msats(), Some(1000));
+
+    let resp = FetchOnePaymentsResponse::error("No Payment records found");
+    assert!(!resp.success);
+    assert!(resp.data.is_none());
+}
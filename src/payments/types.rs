@@ -1,12 +1,21 @@
-use crate::StdResp;
+use crate::{
+    bolt11,
+    custom_deserializer::deserialize_lenient_datetime,
+    errors::ErrorMsg,
+    models::{parse_amount, verify_internal_id, Amount, PaymentStatus, UnitType},
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
 
 pub type PaymentInvoiceResponse = StdResp<Option<PaymentsData>>;
 pub type FetchPaymentsResponse = StdResp<Option<Vec<PaymentsData>>>;
 pub type FetchOnePaymentsResponse = StdResp<Option<PaymentsData>>;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct PaymentsData {
     pub id: String,
     pub fee: Option<String>,
@@ -14,23 +23,93 @@ pub struct PaymentsData {
     pub amount: String,
     pub invoice: Option<String>,
     pub preimage: Option<String>,
-    #[serde(rename = "internalId")]
     pub internal_id: Option<String>,
-    #[serde(rename = "processedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub processed_at: Option<DateTime<Utc>>,
-    #[serde(rename = "confirmedAt")]
+    #[serde(default, deserialize_with = "deserialize_lenient_datetime")]
     pub confirmed_at: Option<DateTime<Utc>>,
     pub description: String,
-    pub status: Option<String>,
+    pub status: Option<PaymentStatus>,
+}
+
+impl PaymentsData {
+    /// Checks that the payment carries the `internal_id` it was sent with.
+    pub fn verify_internal_id(&self, sent: &str) -> Result<(), ErrorMsg> {
+        verify_internal_id(sent, self.internal_id.as_deref())
+    }
+
+    /// The payment amount with its unit, `None` when either can't be parsed.
+    pub fn amount(&self) -> Option<Amount> {
+        Amount::parse(&self.amount, &self.unit)
+    }
+
+    /// The routing fee in the payment's unit, `None` when there is none or it can't be parsed.
+    pub fn fee(&self) -> Option<Amount> {
+        Amount::parse(self.fee.as_deref()?, &self.unit)
+    }
+
+    /// Routing fee as a fraction of the amount, e.g. `0.001` for 0.1%.
+    /// A missing or unparseable fee counts as zero; a zero amount with a fee gives infinity.
+    pub fn fee_ratio(&self) -> f64 {
+        let fee = self.fee_value() as f64;
+        match self.amount().map(|amount| amount.value) {
+            Some(amount) if amount > 0 => fee / amount as f64,
+            _ if fee == 0.0 => 0.0,
+            _ => f64::INFINITY,
+        }
+    }
+
+    /// Returns `false` when the fee is more than `max_ppm` parts per million of the amount,
+    /// so apps can warn about or refuse disproportionate routing fees.
+    pub fn is_fee_reasonable(&self, max_ppm: u64) -> bool {
+        let Some(amount) = self.amount().map(|amount| amount.value) else {
+            return self.fee_value() == 0;
+        };
+        u128::from(self.fee_value()) * 1_000_000 <= u128::from(amount) * u128::from(max_ppm)
+    }
+
+    /// The fee in the payment's own unit, like `amount`, zero when missing or unparseable.
+    fn fee_value(&self) -> u64 {
+        self.fee().map(|fee| fee.value).unwrap_or_default()
+    }
 }
 
 /// Use this struct to create a well crafted json body for normal ligthning bolt 11 payments
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct Payment {
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub description: String,
-    #[serde(rename = "internalId")]
+    /// Echoed back on the payment's `internal_id`, for linking it to your own ledger
     pub internal_id: String,
     pub invoice: String,
+    /// Amount in msats, required for amountless invoices and checked against fixed-amount ones
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<String>,
+}
+
+impl Payment {
+    /// Checks `amount` against the invoice: amountless invoices need one and
+    /// fixed-amount invoices only accept the amount they encode, so a mismatched
+    /// `amount` can't over or underpay. Invoices that can't be decoded locally are
+    /// left for the API to reject.
+    pub fn check_amount(&self) -> Result<(), ErrorMsg> {
+        let Ok(decoded) = bolt11::decode(&self.invoice) else {
+            return Ok(());
+        };
+        match (decoded.amount_msats, &self.amount) {
+            (None, None) => Err(ErrorMsg::BadPaymentAmount(String::from(
+                "amountless invoice requires an amount",
+            ))),
+            (Some(invoice_msats), Some(amount)) => match parse_amount(amount, &UnitType::Msats) {
+                Some(msats) if msats == invoice_msats => Ok(()),
+                _ => Err(ErrorMsg::BadPaymentAmount(format!(
+                    "amount {amount} doesn't match the invoice amount of {invoice_msats} msats"
+                ))),
+            },
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Default for Payment {
@@ -39,6 +118,7 @@ impl Default for Payment {
             description: String::from("using zebedee rust sdk"),
             internal_id: String::from(""),
             invoice: String::from(""),
+            amount: None,
         }
     }
 }
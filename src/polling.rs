@@ -0,0 +1,215 @@
+#[cfg(feature = "payments")]
+use crate::payments::PaymentsData;
+#[cfg(feature = "charges")]
+use crate::{charges::ChargesData, models::ChargeStatus};
+use crate::{errors::ErrorMsg, Result, ZebedeeClient};
+#[cfg(feature = "charges")]
+use futures::{stream, Stream, StreamExt};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// A total time budget shared by several waits, e.g. polling a charge and then the payment
+/// that depends on it, so together they never run past one deadline.
+///
+/// Pass [`PollBudget::limit`] as the `timeout` of each wait: once the budget is spent the
+/// waiters fail with `ErrorMsg::PollTimeout` without polling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollBudget {
+    deadline: Instant,
+}
+
+impl PollBudget {
+    /// Starts a budget of `total` from now.
+    pub fn new(total: Duration) -> Self {
+        PollBudget {
+            deadline: Instant::now() + total,
+        }
+    }
+
+    /// Time left before the deadline, zero once it has passed.
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// `timeout` capped to what's left of the budget.
+    pub fn limit(&self, timeout: Duration) -> Duration {
+        timeout.min(self.remaining())
+    }
+}
+
+impl ZebedeeClient {
+    /// Polls a Charge every `interval` until it is no longer pending, giving up after `timeout`,
+    /// or right away when `timeout` is zero.
+    /// Cancelling `cancel` stops polling right away with `ErrorMsg::Cancelled`.
+    #[cfg(feature = "charges")]
+    pub async fn wait_for_charge<T>(
+        &self,
+        charge_id: T,
+        interval: Duration,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<ChargesData>
+    where
+        T: AsRef<str>,
+    {
+        let charge_id = charge_id.as_ref();
+        if timeout.is_zero() {
+            return Err(ErrorMsg::PollTimeout(charge_id.to_string()).into());
+        }
+
+        let poll = async {
+            loop {
+                let charge = self
+                    .get_charge(charge_id)
+                    .await?
+                    .data
+                    .ok_or_else(|| ErrorMsg::MissingData(charge_id.to_string()))?;
+
+                if charge.status != ChargeStatus::Pending {
+                    return Ok(charge);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        };
+
+        until_cancelled(tokio::time::timeout(timeout, poll), cancel, charge_id)
+            .await?
+            .map_err(|_| ErrorMsg::PollTimeout(charge_id.to_string()))?
+    }
+
+    /// Waits on many Charges at once, polling at most `concurrency` of them at a time.
+    /// Results are returned in the same order as `charge_ids`.
+    #[cfg(feature = "charges")]
+    pub async fn wait_for_charges<T>(
+        &self,
+        charge_ids: &[T],
+        concurrency: usize,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Vec<Result<ChargesData>>
+    where
+        T: AsRef<str>,
+    {
+        stream::iter(charge_ids)
+            .map(|charge_id| self.wait_for_charge(charge_id, interval, timeout, None))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Polls a Charge every `interval`, yielding its status each time it changes, starting with
+    /// the first one observed, e.g. to show progress in a UI. The stream ends after completed,
+    /// expired or error, or after the first failed request. Wrap it in a timeout to bound the
+    /// wait.
+    #[cfg(feature = "charges")]
+    pub fn poll_charge_stream<T>(
+        &self,
+        charge_id: T,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<ChargeStatus>> + '_
+    where
+        T: Into<String>,
+    {
+        let charge_id = charge_id.into();
+
+        // state: the last status yielded and whether the stream is finished
+        stream::unfold(
+            (None, false),
+            move |(last, done): (Option<ChargeStatus>, bool)| {
+                let charge_id = charge_id.clone();
+                async move {
+                    if done {
+                        return None;
+                    }
+                    let mut first_poll = last.is_none();
+                    loop {
+                        if !first_poll {
+                            tokio::time::sleep(interval).await;
+                        }
+                        first_poll = false;
+
+                        let charge = match self.get_charge(&charge_id).await.and_then(|r| {
+                            r.data
+                                .ok_or_else(|| ErrorMsg::MissingData(charge_id.clone()).into())
+                        }) {
+                            Ok(charge) => charge,
+                            Err(e) => return Some((Err(e), (last, true))),
+                        };
+
+                        if last.as_ref() != Some(&charge.status) {
+                            let done = matches!(
+                                charge.status,
+                                ChargeStatus::Completed
+                                    | ChargeStatus::Expired
+                                    | ChargeStatus::Error
+                            );
+                            return Some((Ok(charge.status.clone()), (Some(charge.status), done)));
+                        }
+                    }
+                }
+            },
+        )
+    }
+
+    /// Polls a Payment every `interval` until it settles or fails, giving up after `timeout`,
+    /// or right away when `timeout` is zero.
+    /// Once completed the returned data includes the preimage as proof of payment.
+    /// Cancelling `cancel` stops polling right away with `ErrorMsg::Cancelled`.
+    #[cfg(feature = "payments")]
+    pub async fn wait_for_payment<T>(
+        &self,
+        payment_id: T,
+        interval: Duration,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<PaymentsData>
+    where
+        T: AsRef<str>,
+    {
+        let payment_id = payment_id.as_ref();
+        if timeout.is_zero() {
+            return Err(ErrorMsg::PollTimeout(payment_id.to_string()).into());
+        }
+
+        let poll = async {
+            loop {
+                let payment = self
+                    .get_payment(payment_id)
+                    .await?
+                    .data
+                    .ok_or_else(|| ErrorMsg::MissingData(payment_id.to_string()))?;
+
+                if payment
+                    .status
+                    .as_ref()
+                    .is_some_and(|status| !status.is_in_flight())
+                {
+                    return Ok(payment);
+                }
+                tokio::time::sleep(interval).await;
+            }
+        };
+
+        until_cancelled(tokio::time::timeout(timeout, poll), cancel, payment_id)
+            .await?
+            .map_err(|_| ErrorMsg::PollTimeout(payment_id.to_string()))?
+    }
+}
+
+async fn until_cancelled<F: std::future::Future>(
+    future: F,
+    cancel: Option<&CancellationToken>,
+    id: &str,
+) -> Result<F::Output> {
+    match cancel {
+        Some(cancel) => cancel
+            .run_until_cancelled(future)
+            .await
+            .ok_or_else(|| ErrorMsg::Cancelled(id.to_string()).into()),
+        None => Ok(future.await),
+    }
+}
@@ -0,0 +1,35 @@
+use reqwest::header::HeaderMap;
+
+/// Rate limit state reported by the API in the `X-RateLimit-*` headers of a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Requests allowed in the current window
+    pub limit: u64,
+    /// Requests left in the current window
+    pub remaining: u64,
+    /// `X-RateLimit-Reset` as sent by the API, when present
+    pub reset: Option<u64>,
+}
+
+impl RateLimit {
+    /// Reads the rate limit headers, `None` unless both limit and remaining are present.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        Some(RateLimit {
+            limit: header("x-ratelimit-limit")?,
+            remaining: header("x-ratelimit-remaining")?,
+            reset: header("x-ratelimit-reset"),
+        })
+    }
+
+    /// Whether `remaining` has dropped to `threshold` or below, a cue to slow down before
+    /// the API starts answering with 429s.
+    pub fn is_low(&self, threshold: u64) -> bool {
+        self.remaining <= threshold
+    }
+}
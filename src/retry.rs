@@ -0,0 +1,115 @@
+use reqwest::Method;
+use std::{fmt, sync::Arc, time::Duration};
+
+/// Decides whether a timed out request may be retried, given its method and url path.
+pub type RetryHook = Arc<dyn Fn(&Method, &str) -> bool + Send + Sync>;
+
+/// When to resend a request that timed out. A timeout doesn't tell whether the API acted on
+/// the request, so by default only `GET` and `HEAD` requests are retried and payments never are.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    hook: RetryHook,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: Jitter,
+}
+
+/// Randomizes retry delays so a fleet of clients doesn't retry in lockstep after an outage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Jitter {
+    /// The exact exponential delay
+    #[default]
+    None,
+    /// Anywhere between zero and the exponential delay, spreading retries the most
+    Full,
+    /// At least half the exponential delay plus a random share of the other half
+    Equal,
+}
+
+impl RetryPolicy {
+    /// Retries idempotent requests up to `max_retries` times after a timeout.
+    pub fn new(max_retries: u32) -> Self {
+        RetryPolicy {
+            max_retries,
+            hook: Arc::new(|method, _| matches!(*method, Method::GET | Method::HEAD)),
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            jitter: Jitter::None,
+        }
+    }
+
+    /// Waits `base_delay` before the first retry, doubling for each further one up to
+    /// `max_delay`. Retries are immediate by default.
+    pub fn backoff(mut self, base_delay: Duration, max_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self.max_delay = max_delay;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: Jitter) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Replaces the default decision of which timed out requests are safe to retry.
+    pub fn retry_if<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Method, &str) -> bool + Send + Sync + 'static,
+    {
+        self.hook = Arc::new(hook);
+        self
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub(crate) fn should_retry(&self, attempt: u32, method: &Method, path: &str) -> bool {
+        attempt < self.max_retries && (self.hook)(method, path)
+    }
+
+    /// How long to wait before retry number `attempt`, counting from zero.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        match self.jitter {
+            Jitter::None => delay,
+            Jitter::Full => delay.mul_f64(rand::random::<f64>()),
+            Jitter::Equal => delay / 2 + (delay / 2).mul_f64(rand::random::<f64>()),
+        }
+    }
+}
+
+/// Compares the retry count, delays and jitter. The `retry_if` hook is a closure, which can't
+/// be compared, so it is ignored.
+impl PartialEq for RetryPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_retries == other.max_retries
+            && self.base_delay == other.base_delay
+            && self.max_delay == other.max_delay
+            && self.jitter == other.jitter
+    }
+}
+
+impl Eq for RetryPolicy {}
+
+/// Never retries.
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(0)
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_retries", &self.max_retries)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::{Arc, Mutex};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpListener,
+};
+
+/// A canned HTTP response served by [`MockServer`].
+#[derive(Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn json(status: u16, body: &str) -> Self {
+        MockResponse {
+            status,
+            headers: vec![(
+                String::from("Content-Type"),
+                String::from("application/json"),
+            )],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Local HTTP server answering requests with the given responses in order, repeating the last one.
+pub struct MockServer {
+    pub url: String,
+    /// Raw requests received so far
+    pub requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        tokio::spawn(async move {
+            let mut served = 0;
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(conn) => conn,
+                    Err(_) => return,
+                };
+                let response = responses[served.min(responses.len() - 1)].clone();
+                served += 1;
+                let recorded = recorded.clone();
+
+                tokio::spawn(async move {
+                    let request = read_request(&mut socket).await;
+                    recorded.lock().unwrap().push(request);
+
+                    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+                    for (name, value) in &response.headers {
+                        head.push_str(&format!("{}: {}\r\n", name, value));
+                    }
+                    head.push_str(&format!(
+                        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                        response.body.len()
+                    ));
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(&response.body).await;
+                });
+            }
+        });
+
+        MockServer { url, requests }
+    }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> String {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut chunk).await.unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        let text = String::from_utf8_lossy(&buf);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let content_length = text[..end]
+                .lines()
+                .find_map(|l| {
+                    let (name, value) = l.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&buf).into_owned()
+}
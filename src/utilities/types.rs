@@ -6,26 +6,25 @@ pub type ProdIpsResponse = StdResp<Option<IpData>>;
 pub type BtcToUsdResponse = StdResp<Option<BtcUsdData>>;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct BtcUsdData {
-    #[serde(rename = "btcUsdPrice")]
     pub btc_usd_price: String,
-    #[serde(rename = "btcUsdTimestamp")]
     pub btc_usd_timestamp: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct IpData {
     pub ips: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct RegionIpData {
-    #[serde(rename = "ipAddress")]
     pub ip_address: String,
-    #[serde(rename = "isSupported")]
     pub is_supported: bool,
-    #[serde(rename = "ipCountry")]
     pub ip_country: String,
-    #[serde(rename = "ipRegion")]
     pub ip_region: String,
 }
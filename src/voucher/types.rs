@@ -1,24 +1,26 @@
 use crate::{
-    custom_deserializer::{deserialize_from_m_string, deserialize_from_string},
+    custom_deserializer::{datetime_field, deserialize_from_m_string, deserialize_from_string},
     models::UnitType,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+datetime_field!(deserialize_created_at, "createdAt");
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct VoucherData {
     #[serde(deserialize_with = "deserialize_from_string")]
     pub amount: u64,
     pub code: String,
-    #[serde(rename = "createdAt")]
+    #[serde(deserialize_with = "deserialize_created_at")]
     pub created_at: DateTime<Utc>,
-    #[serde(rename = "createTransactionId")]
     pub create_transaction_id: String,
     pub description: String,
-    #[serde(deserialize_with = "deserialize_from_m_string")]
+    #[serde(default, deserialize_with = "deserialize_from_m_string")]
     pub fee: Option<u64>,
     pub id: String,
     pub unit: UnitType,
-    #[serde(rename = "walletId")]
     pub wallet_id: String,
 }
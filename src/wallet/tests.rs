@@ -1,4 +1,10 @@
-use crate::ZebedeeClient;
+use crate::{
+    command::Command,
+    errors::{ErrorMsg, ZebedeeError},
+    retry::{Jitter, RetryPolicy},
+    test_utils::{MockResponse, MockServer},
+    HttpVersion, ZebedeeClient,
+};
 use std::env;
 
 #[tokio::test]
@@ -18,3 +24,688 @@ async fn test_wallet_details() {
     let r2: u64 = r.parse().unwrap();
     assert!(any_balance.contains(&r2));
 }
+
+#[tokio::test]
+async fn test_wallet_details_connect_error() {
+    // bind and immediately release a port so nothing is listening on it
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(format!("http://{}", addr))
+        .apikey(String::from("apikey"))
+        .build();
+
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Connect(_)));
+}
+
+#[tokio::test]
+async fn test_is_valid_key() {
+    let server = MockServer::start(vec![MockResponse::json(
+        401,
+        r#"{"success":false,"message":"Unauthorized"}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .apikey(String::from("badkey"))
+        .build();
+
+    let r = zebedee_client.is_valid_key().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::InvalidApiKey(_)));
+
+    // a valid key without permission isn't reported as invalid
+    let server = MockServer::start(vec![MockResponse::json(
+        403,
+        r#"{"success":false,"message":"Forbidden"}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+    let r = zebedee_client.is_valid_key().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Forbidden { .. }));
+
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"},"message":"Successfully retrieved Wallet."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    assert!(zebedee_client.is_valid_key().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_execute_get_wallet() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"},"message":"Successfully retrieved Wallet."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.execute(Command::GetWallet).await.unwrap();
+    assert_eq!(r["data"]["balance"], "1000");
+}
+
+#[tokio::test]
+async fn test_custom_apikey_header() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .apikey(String::from("secret"))
+        .apikey_header(String::from("x-gateway-key"))
+        .build();
+
+    zebedee_client.get_wallet_details().await.unwrap();
+
+    let request = server.requests.lock().unwrap()[0].to_lowercase();
+    assert!(request.contains("x-gateway-key: secret"));
+    assert!(!request.contains("apikey:"));
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            200,
+            r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#,
+        ),
+        MockResponse::json(
+            500,
+            r#"{"success":false,"message":"Internal Server Error"}"#,
+        ),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    zebedee_client.get_wallet_details().await.unwrap();
+    zebedee_client
+        .clone()
+        .get_wallet_details()
+        .await
+        .unwrap_err();
+
+    let metrics = zebedee_client.metrics();
+    assert_eq!(metrics.requests(), 2);
+    assert_eq!(metrics.server_errors(), 1);
+    assert_eq!(metrics.client_errors(), 0);
+}
+
+#[tokio::test]
+async fn test_shared_client() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#,
+    )])
+    .await;
+
+    for apikey in ["first", "second"] {
+        let zebedee_client = ZebedeeClient::shared(String::from(apikey)).domain(server.url.clone());
+        zebedee_client.get_wallet_details().await.unwrap();
+    }
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests[0].to_lowercase().contains("apikey: first"));
+    assert!(requests[1].to_lowercase().contains("apikey: second"));
+}
+
+#[tokio::test]
+async fn test_shared_client_pool() {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // a keep-alive server counting the connections it accepts
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicUsize::new(0));
+    let accepted = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            accepted.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut buf = [0u8; 4096];
+                while let Ok(1..) = socket.read(&mut buf).await {
+                    let body = r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#;
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                        body.len()
+                    );
+                    if socket.write_all(response.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    for apikey in ["first", "second"] {
+        let zebedee_client = ZebedeeClient::shared(String::from(apikey)).domain(url.clone());
+        zebedee_client.get_wallet_details().await.unwrap();
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+
+    // clients from new() each have their own pool
+    for apikey in ["first", "second"] {
+        let zebedee_client = ZebedeeClient::new()
+            .apikey(String::from(apikey))
+            .domain(url.clone())
+            .build();
+        zebedee_client.get_wallet_details().await.unwrap();
+    }
+    assert_eq!(connections.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_locale() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url.clone())
+        .locale(String::from("pt-BR"))
+        .build();
+    zebedee_client.get_wallet_details().await.unwrap();
+
+    let zebedee_client = ZebedeeClient::new().domain(server.url.clone()).build();
+    zebedee_client.get_wallet_details().await.unwrap();
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests[0]
+        .to_lowercase()
+        .contains("accept-language: pt-br"));
+    assert!(!requests[1].to_lowercase().contains("accept-language"));
+}
+
+#[tokio::test]
+async fn test_max_body_size() {
+    let body = format!(
+        r#"{{"success":true,"data":{{"unit":"msats","balance":"1000"}},"message":"{}"}}"#,
+        "a".repeat(4096)
+    );
+    let server = MockServer::start(vec![MockResponse::json(200, &body)]).await;
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url.clone())
+        .max_body_size(1024)
+        .build();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::BodyTooLarge(1024))));
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .max_body_size(8192)
+        .build();
+    assert!(zebedee_client.get_wallet_details().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_gzip_response() {
+    // the test_max_body_size body, 4166 bytes once decoded
+    const GZIP_BODY: &[u8] = b"\x1f\x8b\x08\x00\x00\x00\x00\x00\x02\x03\xed\xcb\x31\x0e\x80\x20\x10\x44\xd1\xbb\x4c\x4d\x81\x2d\xb7\x19\x91\x18\x13\xa1\x70\x97\x8a\x70\x77\xf1\x1a\x66\x5e\xf9\x93\x3f\x60\x3d\xe7\x62\x86\xe4\x4f\x2f\x01\x07\x9d\x48\x03\xbd\x5d\x8e\x84\x6a\x74\x43\xc0\xce\x9b\x2d\x97\x55\xb6\x18\x23\x66\x40\x5d\x13\xcf\xaf\x50\x44\x44\x44\x44\x44\x44\x44\x7e\x0f\xf3\x05\x1e\xb0\x06\xb5\x46\x10\x00\x00";
+    let gzip_response = |body: &[u8]| MockResponse {
+        status: 200,
+        headers: vec![
+            (
+                String::from("Content-Type"),
+                String::from("application/json"),
+            ),
+            (String::from("Content-Encoding"), String::from("gzip")),
+        ],
+        body: body.to_vec(),
+    };
+    let server = MockServer::start(vec![gzip_response(GZIP_BODY)]).await;
+
+    let zebedee_client = ZebedeeClient::new().domain(server.url.clone()).build();
+    let r = zebedee_client.get_wallet_details().await.unwrap();
+    assert_eq!(r.data.unwrap().balance, "1000");
+    assert!(server.requests.lock().unwrap()[0].contains("accept-encoding: gzip, deflate\r\n"));
+
+    // the limit applies to the decoded body, not just the 97 bytes on the wire
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url.clone())
+        .max_body_size(1024)
+        .build();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::BodyTooLarge(1024))));
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .max_body_size(8192)
+        .build();
+    assert!(zebedee_client.get_wallet_details().await.is_ok());
+
+    // a body that doesn't match its encoding is reported as such
+    let server = MockServer::start(vec![gzip_response(br#"{"success":true,"data":{}}"#)]).await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(
+        matches!(r, ZebedeeError::Msg(ErrorMsg::BadContentEncoding(ref encoding, _)) if encoding == "gzip")
+    );
+}
+
+#[tokio::test]
+async fn test_unauthorized_vs_forbidden() {
+    let server = MockServer::start(vec![
+        MockResponse::json(401, r#"{"success":false,"message":"Unauthorized"}"#),
+        MockResponse::json(403, r#"{"success":false,"message":"Forbidden"}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(
+        matches!(r, ZebedeeError::Unauthorized { ref endpoint, .. } if endpoint == "/v0/wallet")
+    );
+
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Forbidden { ref endpoint, .. } if endpoint == "/v0/wallet"));
+}
+
+#[tokio::test]
+async fn test_non_utf8_body() {
+    let server = MockServer::start(vec![MockResponse {
+        status: 502,
+        headers: vec![(String::from("Content-Type"), String::from("text/html"))],
+        body: vec![0x3c, 0x68, 0xff, 0xfe, 0x3e],
+    }])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(
+        matches!(r, ZebedeeError::Msg(ErrorMsg::BadEncoding(ref content_type, _)) if content_type == "text/html")
+    );
+}
+
+#[tokio::test]
+async fn test_client_eq() {
+    let a = ZebedeeClient::new().apikey(String::from("key-a")).build();
+    let b = ZebedeeClient::new()
+        .apikey(String::from("key-a"))
+        .reqw_cli(reqwest::Client::new())
+        .build();
+    assert_eq!(a, b);
+    assert_eq!(a, a.clone());
+
+    let c = b.clone().apikey(String::from("key-b")).build();
+    assert_ne!(a, c);
+    let d = b.domain(String::from("https://sandbox.example")).build();
+    assert_ne!(a, d);
+    let e = a
+        .clone()
+        .retry_policy(RetryPolicy::new(0).jitter(Jitter::Full))
+        .build();
+    assert_ne!(a, e);
+}
+
+#[tokio::test]
+async fn test_retry_policy() {
+    // a server that accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hung_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+    let reqw_cli = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(50))
+        .build()
+        .unwrap();
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(hung_url.clone())
+        .reqw_cli(reqw_cli.clone())
+        .retry_policy(RetryPolicy::new(2))
+        .build();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Timeout(_)));
+    assert_eq!(zebedee_client.metrics().requests(), 3);
+    assert_eq!(zebedee_client.metrics().retries(), 2);
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(hung_url)
+        .reqw_cli(reqw_cli)
+        .retry_policy(RetryPolicy::new(2).retry_if(|_, path| path != "/v0/wallet"))
+        .build();
+    zebedee_client.get_wallet_details().await.unwrap_err();
+    assert_eq!(zebedee_client.metrics().requests(), 1);
+}
+
+#[tokio::test]
+async fn test_warmup() {
+    let server = MockServer::start(vec![MockResponse::json(404, "")]).await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    zebedee_client.warmup().await.unwrap();
+    assert!(server.requests.lock().unwrap()[0].starts_with("HEAD / "));
+    assert_eq!(zebedee_client.metrics().requests(), 1);
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let zebedee_client = ZebedeeClient::new()
+        .domain(format!("http://{}", addr))
+        .build();
+    let r = zebedee_client.warmup().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Connect(_)));
+}
+
+#[tokio::test]
+async fn test_clone_with_timeout() {
+    // a server that accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hung_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let zebedee_client = ZebedeeClient::new().domain(hung_url).build();
+    let fast = zebedee_client.clone_with_timeout(std::time::Duration::from_millis(50));
+    assert_ne!(zebedee_client, fast);
+
+    let r = fast.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Timeout(_)));
+    // metrics are shared with the original client
+    assert_eq!(zebedee_client.metrics().transport_errors(), 1);
+}
+
+#[tokio::test]
+async fn test_connect_timeout() {
+    // a server that accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let hung_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(hung_url)
+        .connect_timeout(std::time::Duration::from_millis(20))
+        .timeout(std::time::Duration::from_millis(300))
+        .build();
+    assert_ne!(zebedee_client, ZebedeeClient::new().build());
+
+    // connecting succeeds, so only the overall timeout ends the wait for a response
+    let started = std::time::Instant::now();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Timeout(_)));
+    assert!(started.elapsed() >= std::time::Duration::from_millis(250));
+}
+
+#[tokio::test]
+async fn test_is_retryable() {
+    let mut bad_gateway = MockResponse::json(502, "<html>Bad Gateway</html>");
+    bad_gateway.headers.clear();
+    let server = MockServer::start(vec![
+        bad_gateway,
+        MockResponse::json(429, r#"{"success":false,"message":"Slow down"}"#),
+        MockResponse::json(400, r#"{"success":false,"message":"Bad request"}"#),
+        // a gateway's error body isn't mistaken for an OAuth error outside the token endpoint
+        MockResponse::json(429, r#"{"error":"Too many requests"}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(
+        r,
+        ZebedeeError::Api(ref e) if e.status == Some(502) && e.body.as_deref() == Some("<html>Bad Gateway</html>")
+    ));
+    assert!(r.is_retryable());
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(
+        matches!(r, ZebedeeError::RateLimited { ref error, .. } if error.message == "Slow down")
+    );
+    assert!(r.is_retryable());
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Api(ref e) if e.status == Some(400)));
+    assert!(!r.is_retryable());
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::RateLimited { .. }));
+    assert!(r.is_retryable());
+
+    let r = ZebedeeClient::new()
+        .domain(String::from("http://127.0.0.1:1"))
+        .build()
+        .get_wallet_details()
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(r, ZebedeeError::Connect(_)));
+    assert!(r.is_retryable());
+    assert!(!ZebedeeError::Msg(ErrorMsg::MissingData(String::from("/v0/wallet"))).is_retryable());
+}
+
+#[tokio::test]
+async fn test_chunked_response() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // a server streaming its answer in chunks, without a Content-Length
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let body = r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#;
+            let (first, second) = body.split_at(20);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n{:x}\r\n{first}\r\n{:x}\r\n{second}\r\n0\r\n\r\n",
+                first.len(),
+                second.len()
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+
+    let zebedee_client = ZebedeeClient::new().domain(url.clone()).build();
+    let r = zebedee_client.get_wallet_details().await.unwrap();
+    assert_eq!(r.data.unwrap().balance, "1000");
+
+    // the size limit is enforced while streaming, as there is no length to check upfront
+    let zebedee_client = ZebedeeClient::new().domain(url).max_body_size(30).build();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Msg(ErrorMsg::BodyTooLarge(30))));
+}
+
+#[tokio::test]
+async fn test_success_false_with_ok_status() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":false,"data":null,"message":"Wallet is frozen"}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(
+        matches!(r, ZebedeeError::Api(ref e) if e.message == "Wallet is frozen" && e.status.is_none())
+    );
+    assert!(!r.is_retryable());
+}
+
+#[tokio::test]
+async fn test_rate_limit() {
+    let body = r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#;
+    let mut limited = MockResponse::json(200, body);
+    limited.headers.extend([
+        (String::from("X-RateLimit-Limit"), String::from("100")),
+        (String::from("X-RateLimit-Remaining"), String::from("3")),
+        (String::from("X-RateLimit-Reset"), String::from("30")),
+    ]);
+    let server = MockServer::start(vec![limited, MockResponse::json(200, body)]).await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+    assert_eq!(zebedee_client.rate_limit(), None);
+
+    zebedee_client.get_wallet_details().await.unwrap();
+    let rate_limit = zebedee_client.clone().rate_limit().unwrap();
+    assert_eq!(rate_limit.limit, 100);
+    assert_eq!(rate_limit.remaining, 3);
+    assert_eq!(rate_limit.reset, Some(30));
+    assert!(rate_limit.is_low(5));
+
+    // responses without the headers keep the last known state
+    zebedee_client.get_wallet_details().await.unwrap();
+    assert_eq!(zebedee_client.rate_limit(), Some(rate_limit));
+}
+
+#[tokio::test]
+async fn test_http_version() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#,
+    )])
+    .await;
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url.clone())
+        .http_version(HttpVersion::Http1Only)
+        .build();
+    zebedee_client.get_wallet_details().await.unwrap();
+
+    // the mock only speaks HTTP/1.1, but it still sees the HTTP/2 connection preface
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url.clone())
+        .http_version(HttpVersion::Http2PriorKnowledge)
+        .build();
+    zebedee_client.get_wallet_details().await.unwrap_err();
+
+    // a client supplied with reqw_cli is kept as is
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .reqw_cli(reqwest::Client::new())
+        .http_version(HttpVersion::Http2PriorKnowledge)
+        .build();
+    zebedee_client.get_wallet_details().await.unwrap();
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests[0].starts_with("GET /v0/wallet HTTP/1.1"));
+    assert!(requests[1].starts_with("PRI * HTTP/2.0"));
+    assert!(requests[2].starts_with("GET /v0/wallet HTTP/1.1"));
+}
+
+#[tokio::test]
+async fn test_custom_request() {
+    let server = MockServer::start(vec![
+        MockResponse::json(200, r#"{"success":true,"data":{"streak":7}}"#),
+        MockResponse::json(400, r#"{"success":false,"message":"Bad streak"}"#),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new()
+        .domain(server.url)
+        .apikey(String::from("secret"))
+        .build();
+
+    let request = zebedee_client
+        .request(reqwest::Method::POST, "/v0/streaks")
+        .unwrap()
+        .json(&serde_json::json!({ "days": 7 }));
+    let r: crate::StdResp<serde_json::Value> = zebedee_client.send_request(request).await.unwrap();
+    assert_eq!(r.data["streak"], 7);
+
+    let request = zebedee_client
+        .request(reqwest::Method::GET, "/v0/streaks")
+        .unwrap();
+    let r = zebedee_client
+        .send_request::<crate::StdResp<serde_json::Value>>(request)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(r, ZebedeeError::Api(ref e) if e.message == "Bad streak"));
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests[0].starts_with("POST /v0/streaks "));
+    assert!(requests[0].to_lowercase().contains("apikey: secret"));
+    assert_eq!(zebedee_client.metrics().requests(), 2);
+}
+
+#[tokio::test]
+async fn test_base_url_and_api_version() {
+    let zebedee_client = ZebedeeClient::new()
+        .domain(String::from("https://sandbox.example"))
+        .build();
+    assert_eq!(zebedee_client.base_url(), "https://sandbox.example");
+    assert_eq!(zebedee_client.api_version(), "v0");
+    let prefix = format!("/{}/", zebedee_client.api_version());
+    assert!(crate::endpoints::WALLET.starts_with(&prefix));
+    assert_eq!(ZebedeeClient::new().base_url(), "https://api.zebedee.io");
+    assert_eq!(
+        ZebedeeClient::with_base_url(String::from("key"), String::from("https://sandbox.example")),
+        zebedee_client.apikey(String::from("key")).build()
+    );
+}
+
+#[tokio::test]
+async fn test_retry_backoff_jitter() {
+    use std::time::Duration;
+
+    let policy = RetryPolicy::new(5).backoff(Duration::from_millis(100), Duration::from_secs(1));
+    assert_eq!(policy.delay(0), Duration::from_millis(100));
+    assert_eq!(policy.delay(2), Duration::from_millis(400));
+    assert_eq!(policy.delay(10), Duration::from_secs(1));
+    assert_eq!(RetryPolicy::new(5).delay(3), Duration::ZERO);
+
+    let full = policy.clone().jitter(Jitter::Full);
+    let equal = policy.jitter(Jitter::Equal);
+    let full_delays: Vec<Duration> = (0..20).map(|_| full.delay(2)).collect();
+    assert!(full_delays.iter().all(|d| *d <= Duration::from_millis(400)));
+    assert!(full_delays.iter().any(|d| *d != full_delays[0]));
+    assert!((0..20)
+        .map(|_| equal.delay(2))
+        .all(|d| d >= Duration::from_millis(200) && d <= Duration::from_millis(400)));
+}
+
+#[tokio::test]
+async fn test_fallback_domain() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":{"unit":"msats","balance":"1000"}}"#,
+    )])
+    .await;
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let down = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    let zebedee_client = ZebedeeClient::new()
+        .domain(down.clone())
+        .fallback_domain(server.url.clone())
+        .build();
+    zebedee_client.get_wallet_details().await.unwrap();
+    let request = zebedee_client
+        .request(reqwest::Method::GET, "/v0/wallet")
+        .unwrap()
+        .query(&[("note", "a/b c")]);
+    zebedee_client
+        .send_request::<serde_json::Value>(request)
+        .await
+        .unwrap();
+    assert_eq!(zebedee_client.metrics().transport_errors(), 2);
+
+    let requests = server.requests.lock().unwrap().clone();
+    assert!(requests[0].starts_with("GET /v0/wallet "));
+    assert!(requests[1].starts_with("GET /v0/wallet?note=a%2Fb+c "));
+
+    // without a fallback the connection error is returned
+    let zebedee_client = ZebedeeClient::new().domain(down).build();
+    let r = zebedee_client.get_wallet_details().await.err().unwrap();
+    assert!(matches!(r, ZebedeeError::Connect(_)));
+}
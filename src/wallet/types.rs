@@ -4,6 +4,7 @@ use crate::StdResp;
 pub type WalletInfoResponse = StdResp<Option<WalletData>>;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
 pub struct WalletData {
     pub unit: String,
     pub balance: String,
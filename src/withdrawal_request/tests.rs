@@ -1,5 +1,8 @@
 use super::*;
-use crate::ZebedeeClient;
+use crate::{
+    test_utils::{MockResponse, MockServer},
+    ZebedeeClient,
+};
 use std::env;
 
 #[tokio::test]
@@ -27,7 +30,7 @@ async fn test_get_withdrawal_requests() {
         env::var("ZBD_ENV").unwrap_or_else(|_| String::from("https://api.zebedee.io"));
     let zebedee_client = ZebedeeClient::new().domain(zbdenv).apikey(apikey).build();
 
-    let r = zebedee_client.get_withdrawal_requests().await.unwrap();
+    let r = zebedee_client.get_withdrawal_requests(None).await.unwrap();
     assert!(r.success);
 }
 #[tokio::test]
@@ -52,3 +55,210 @@ async fn test_get_withdrawal_request() {
         .unwrap();
     assert!(r2.success);
 }
+
+#[tokio::test]
+async fn test_get_withdrawal_requests_empty() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":[],"message":"Fetched Withdrawal Requests."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let r = zebedee_client.get_withdrawal_requests(None).await.unwrap();
+    assert!(r.success);
+    assert!(r.is_empty());
+    assert_eq!(r.data.unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_withdrawal_request_without_callback_url() {
+    let body = r#"{
+        "id": "1b6f4e5e-7c0c-4a3f-9f55-0b6c3cfd8d2a",
+        "unit": "msats",
+        "amount": "10000",
+        "createdAt": "2023-05-01T12:00:00.000Z",
+        "expiresAt": "2023-05-01T12:05:00.000Z",
+        "internalId": "",
+        "description": "withdraw",
+        "status": "pending",
+        "invoice": {
+            "request": "lnurl1",
+            "fastRequest": "lnurl1",
+            "uri": "lightning:lnurl1",
+            "fastUri": "lightning:lnurl1"
+        }
+    }"#;
+
+    let withdrawal: WithdrawalRequestsData = serde_json::from_str(body).unwrap();
+    assert!(withdrawal.callback_url.is_none());
+}
+
+#[cfg(feature = "charges")]
+#[tokio::test]
+async fn test_withdrawal_request_from_charge() {
+    let charge = crate::charges::Charge {
+        amount: String::from("10000"),
+        internal_id: String::from("order-1234"),
+        ..Default::default()
+    };
+    let withdrawal_request = WithdrawalReqest::try_from(charge).unwrap();
+    assert_eq!(withdrawal_request.amount, "10000");
+    assert_eq!(withdrawal_request.internal_id, "order-1234");
+
+    let charge = crate::charges::Charge::from(withdrawal_request);
+    assert_eq!(charge.amount, "10000");
+
+    let usd = crate::charges::Charge::usd(crate::models::UsdAmount(500));
+    assert!(WithdrawalReqest::try_from(usd).is_err());
+}
+
+#[tokio::test]
+async fn test_withdrawal_request_bad_timestamp() {
+    let body = r#"{
+        "id": "1b6f4e5e-7c0c-4a3f-9f55-0b6c3cfd8d2a",
+        "unit": "msats",
+        "amount": "10000",
+        "createdAt": "2023-05-01T12:00:00.000Z",
+        "expiresAt": "soon",
+        "internalId": "",
+        "description": "withdraw",
+        "status": "pending",
+        "invoice": {
+            "request": "lnurl1",
+            "fastRequest": "lnurl1",
+            "uri": "lightning:lnurl1",
+            "fastUri": "lightning:lnurl1"
+        }
+    }"#;
+
+    let err = serde_json::from_str::<WithdrawalRequestsData>(body)
+        .err()
+        .unwrap();
+    assert!(err
+        .to_string()
+        .contains(r#"invalid timestamp "soon" for `expiresAt`"#));
+}
+
+#[tokio::test]
+async fn test_withdrawal_request_lnurl() {
+    let body = r#"{
+        "id": "1b6f4e5e-7c0c-4a3f-9f55-0b6c3cfd8d2a",
+        "unit": "msats",
+        "amount": "10000",
+        "createdAt": "2023-05-01T12:00:00.000Z",
+        "expiresAt": "2023-05-01T12:05:00.000Z",
+        "internalId": "",
+        "description": "withdraw",
+        "status": "pending",
+        "invoice": {
+            "request": "lnurl1dp68gurn8ghj7",
+            "fastRequest": "lnurl1fast",
+            "uri": "lightning:lnurl1dp68gurn8ghj7",
+            "fastUri": "zbd:lnurl1fast"
+        }
+    }"#;
+
+    let withdrawal: WithdrawalRequestsData = serde_json::from_str(body).unwrap();
+    assert_eq!(withdrawal.lnurl(), "lnurl1dp68gurn8ghj7");
+    assert_eq!(withdrawal.lnurl_qr_data(), "LIGHTNING:LNURL1DP68GURN8GHJ7");
+}
+
+#[tokio::test]
+async fn test_get_or_create_withdrawal_request() {
+    let withdrawal = |id: &str, internal_id: &str, status: &str| {
+        format!(
+            r#"{{"id":"{id}","unit":"msats","amount":"10000","createdAt":"2023-05-01T12:00:00.000Z","expiresAt":"2023-05-01T12:05:00.000Z","internalId":"{internal_id}","description":"reward","status":"{status}","invoice":{{"request":"lnurl1","fastRequest":"lnurl1","uri":"lightning:lnurl1","fastUri":"lightning:lnurl1"}}}}"#
+        )
+    };
+    let list = format!(
+        r#"{{"success":true,"data":[{},{}]}}"#,
+        withdrawal("w1", "reward-1", "expired"),
+        withdrawal("w2", "reward-1", "pending"),
+    );
+    let server = MockServer::start(vec![
+        MockResponse::json(200, &list),
+        MockResponse::json(200, &list),
+        MockResponse::json(
+            200,
+            &format!(
+                r#"{{"success":true,"data":{}}}"#,
+                withdrawal("w3", "reward-2", "pending")
+            ),
+        ),
+    ])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let reward = WithdrawalReqest {
+        amount: String::from("10000"),
+        internal_id: String::from("reward-1"),
+        ..Default::default()
+    };
+    let r = zebedee_client
+        .get_or_create_withdrawal_request(&reward)
+        .await
+        .unwrap();
+    assert_eq!(r.data.unwrap().id, "w2");
+
+    let reward = WithdrawalReqest {
+        internal_id: String::from("reward-2"),
+        ..reward
+    };
+    let r = zebedee_client
+        .get_or_create_withdrawal_request(&reward)
+        .await
+        .unwrap();
+    assert_eq!(r.data.unwrap().id, "w3");
+
+    let requests = server.requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    assert!(requests[2].starts_with("POST /v0/withdrawal-requests "));
+}
+
+#[tokio::test]
+async fn test_get_withdrawal_requests_paged() {
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        r#"{"success":true,"data":[],"message":"Fetched Withdrawal Requests."}"#,
+    )])
+    .await;
+    let zebedee_client = ZebedeeClient::new().domain(server.url).build();
+
+    let params = crate::models::ListParams {
+        page: Some(2),
+        limit: Some(50),
+        ..Default::default()
+    };
+    zebedee_client
+        .get_withdrawal_requests(Some(&params))
+        .await
+        .unwrap();
+    zebedee_client.get_withdrawal_requests(None).await.unwrap();
+
+    let requests = server.requests.lock().unwrap();
+    assert!(requests[0].starts_with("GET /v0/withdrawal-requests?page=2&limit=50 "));
+    assert!(requests[1].starts_with("GET /v0/withdrawal-requests "));
+}
+
+#[tokio::test]
+async fn test_withdrawal_request_wire_format() {
+    let withdrawal_request = WithdrawalReqest {
+        expires_in: Some(300),
+        amount: String::from("5000"),
+        description: String::from("prize"),
+        internal_id: String::from("order-1"),
+        callback_url: String::from("https://example.com/callback"),
+    };
+
+    assert_eq!(
+        serde_json::to_value(&withdrawal_request).unwrap(),
+        serde_json::json!({
+            "expiresIn": 300,
+            "amount": "5000",
+            "description": "prize",
+            "internalId": "order-1",
+            "callbackUrl": "https://example.com/callback",
+        })
+    );
+}
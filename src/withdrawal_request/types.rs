@@ -1,56 +1,110 @@
-use crate::StdResp;
+use crate::{
+    custom_deserializer::datetime_field,
+    errors::ErrorMsg,
+    models::{generate_internal_id, verify_internal_id, Amount, Msats, WithdrawalStatus},
+    StdResp,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+datetime_field!(deserialize_created_at, "createdAt");
+datetime_field!(deserialize_expires_at, "expiresAt");
 
 pub type CreateWithdrawalResponse = StdResp<Option<WithdrawalRequestsData>>;
 pub type FetchWithdrawalsResponse = StdResp<Option<Vec<WithdrawalRequestsData>>>;
 pub type FetchOneWithdrawalResponse = StdResp<Option<WithdrawalRequestsData>>;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct WithdrawInvoiceData {
     pub request: String,
-    #[serde(rename = "fastRequest")]
     pub fast_request: String,
     pub uri: String,
-    #[serde(rename = "fastUri")]
     pub fast_uri: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "mock"), derive(Default))]
+#[serde(rename_all = "camelCase")]
 pub struct WithdrawalRequestsData {
     pub id: String,
     pub unit: String,
     pub amount: String,
-    #[serde(rename = "createdAt")]
+    #[serde(deserialize_with = "deserialize_created_at")]
     pub created_at: DateTime<Utc>,
-    #[serde(rename = "expiresAt")]
+    #[serde(deserialize_with = "deserialize_expires_at")]
     pub expires_at: DateTime<Utc>,
-    #[serde(rename = "internalId")]
     pub internal_id: String,
     pub description: String,
-    #[serde(rename = "callbackUrl")]
-    pub callback_url: String,
-    pub status: String,
+    pub callback_url: Option<String>,
+    pub status: WithdrawalStatus,
     pub invoice: WithdrawInvoiceData,
 }
 
+impl WithdrawalRequestsData {
+    /// Checks that the created withdrawal request carries the `internal_id` it was created with.
+    pub fn verify_internal_id(&self, sent: &str) -> Result<(), ErrorMsg> {
+        verify_internal_id(sent, Some(&self.internal_id))
+    }
+
+    /// The withdrawal amount with its unit, `None` when either can't be parsed.
+    pub fn amount(&self) -> Option<Amount> {
+        Amount::parse(&self.amount, &self.unit)
+    }
+
+    /// The LNURL-withdraw string (`lnurl1...`) the claimer's wallet needs. The `fast_*`
+    /// variants are only understood by the ZEBEDEE app.
+    pub fn lnurl(&self) -> &str {
+        &self.invoice.request
+    }
+
+    /// Contents for a withdraw QR code: the `lightning:` URI, uppercased so QR encoders can
+    /// use the denser alphanumeric mode. Pass it to the QR library of your choice.
+    pub fn lnurl_qr_data(&self) -> String {
+        format!("LIGHTNING:{}", self.lnurl().to_uppercase())
+    }
+}
+
 /// Use this struct to create a well crafted json body for withdrawal requests
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct WithdrawalReqest {
-    #[serde(rename = "expiresIn")]
-    pub expires_in: u32,
+    /// Left out of the request when `None` so the API's default expiry applies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u32>,
     pub amount: String,
+    #[validate(length(max = 150, message = "must be at most 150 characters"))]
     pub description: String,
-    #[serde(rename = "internalId")]
     pub internal_id: String,
-    #[serde(rename = "callbackUrl")]
     pub callback_url: String,
 }
 
+impl WithdrawalReqest {
+    /// Creates a withdrawal request for `amount` msats, e.g.
+    /// `WithdrawalReqest::msats(Msats::from_sats(10))` for 10 sats.
+    pub fn msats(amount: impl Into<Msats>) -> Self {
+        WithdrawalReqest {
+            amount: amount.into().0.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a withdrawal request with a random UUID `internal_id`, for correlating it later
+    /// when you don't have ids of your own. Store `internal_id` before creating the request.
+    pub fn with_generated_internal_id() -> Self {
+        WithdrawalReqest {
+            internal_id: generate_internal_id(),
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for WithdrawalReqest {
     fn default() -> WithdrawalReqest {
         WithdrawalReqest {
-            expires_in: 300,
+            expires_in: None,
             amount: String::from("0"),
             description: String::from("using zebedee rust sdk"),
             internal_id: String::from(""),
@@ -58,3 +112,51 @@ impl Default for WithdrawalReqest {
         }
     }
 }
+
+#[cfg(feature = "charges")]
+impl From<WithdrawalReqest> for crate::charges::Charge {
+    fn from(value: WithdrawalReqest) -> Self {
+        crate::charges::Charge {
+            expires_in: value.expires_in,
+            amount: value.amount,
+            description: value.description,
+            internal_id: value.internal_id,
+            callback_url: value.callback_url,
+            unit: None,
+            min_amount: None,
+            max_amount: None,
+            callback_secret: None,
+        }
+    }
+}
+
+/// Fails for charges in another unit than msats or with an amount range, neither of which
+/// withdrawal requests support.
+#[cfg(feature = "charges")]
+impl TryFrom<crate::charges::Charge> for WithdrawalReqest {
+    type Error = ErrorMsg;
+
+    fn try_from(value: crate::charges::Charge) -> Result<Self, Self::Error> {
+        if value.min_amount.is_some() || value.max_amount.is_some() {
+            return Err(ErrorMsg::BadPayloadData(String::from(
+                "withdrawal requests have a fixed amount",
+            )));
+        }
+        let callback_url = match &value.callback_secret {
+            Some(secret) => crate::callbacks::append_secret(&value.callback_url, secret)?,
+            None => value.callback_url,
+        };
+        match value.unit {
+            None | Some(crate::models::UnitType::Msats) => Ok(WithdrawalReqest {
+                expires_in: value.expires_in,
+                amount: value.amount,
+                description: value.description,
+                internal_id: value.internal_id,
+                callback_url,
+            }),
+            Some(unit) => Err(ErrorMsg::BadPayloadData(format!(
+                "withdrawal requests are in msats, not {unit:?}"
+            ))),
+        }
+    }
+}